@@ -0,0 +1,127 @@
+//! Compares the varint and delta encodings with fixed-width big-endian encoding.
+//!
+//! Run with `cargo bench --bench varint`. This uses a plain timing loop rather than a benchmark
+//! framework, so treat the timings as rough comparisons, not precise measurements.
+
+use rocksdb_table::codec::varint::{
+    decode_deltas, decode_varint, encode_deltas, encode_varint_into,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const COUNT: usize = 100_000;
+const ITERATIONS: u32 = 50;
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    // Warm up before measuring.
+    f();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+
+    start.elapsed() / ITERATIONS
+}
+
+fn report(name: &str, size: usize, encode: Duration, decode: Duration) {
+    println!(
+        "{name:<24} {size:>10} bytes  encode {:>10.3?}  decode {:>10.3?}",
+        encode, decode
+    );
+}
+
+fn encode_fixed(values: &[u64]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(values.len() * 8);
+    for value in values {
+        buffer.extend_from_slice(&value.to_be_bytes());
+    }
+    buffer
+}
+
+fn decode_fixed(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn encode_varints(values: &[u64]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(values.len());
+    for value in values {
+        encode_varint_into(*value, &mut buffer);
+    }
+    buffer
+}
+
+fn decode_varints(bytes: &[u8]) -> Vec<u64> {
+    let mut values = Vec::with_capacity(bytes.len());
+    let mut remaining = bytes;
+    while !remaining.is_empty() {
+        let (value, len) = decode_varint(remaining).unwrap();
+        values.push(value);
+        remaining = &remaining[len..];
+    }
+    values
+}
+
+fn compare(label: &str, values: &[u64]) {
+    println!("{label} ({} values)", values.len());
+
+    let fixed = encode_fixed(values);
+    report(
+        "fixed-width",
+        fixed.len(),
+        time(|| {
+            black_box(encode_fixed(black_box(values)));
+        }),
+        time(|| {
+            black_box(decode_fixed(black_box(&fixed)));
+        }),
+    );
+
+    let varints = encode_varints(values);
+    assert_eq!(decode_varints(&varints), values);
+    report(
+        "varint",
+        varints.len(),
+        time(|| {
+            black_box(encode_varints(black_box(values)));
+        }),
+        time(|| {
+            black_box(decode_varints(black_box(&varints)));
+        }),
+    );
+
+    let deltas = encode_deltas(values);
+    assert_eq!(decode_deltas(&deltas).unwrap(), values);
+    report(
+        "delta",
+        deltas.len(),
+        time(|| {
+            black_box(encode_deltas(black_box(values)));
+        }),
+        time(|| {
+            black_box(decode_deltas(black_box(&deltas)).unwrap());
+        }),
+    );
+
+    println!();
+}
+
+fn main() {
+    // Small counts, the typical use case for varints.
+    let counts = (0..COUNT as u64).map(|i| i % 1000).collect::<Vec<_>>();
+    // Sorted millisecond timestamps with small gaps, the typical use case for deltas.
+    let timestamps = (0..COUNT as u64)
+        .map(|i| 1_700_000_000_000 + i * 37)
+        .collect::<Vec<_>>();
+    // Large values, where varints are larger than fixed-width encodings.
+    let large = (0..COUNT as u64)
+        .map(|i| u64::MAX - i.wrapping_mul(0x9e37_79b9))
+        .collect::<Vec<_>>();
+
+    compare("Small counts", &counts);
+    compare("Sorted timestamps", &timestamps);
+    compare("Large values", &large);
+}
@@ -0,0 +1,104 @@
+//! Asynchronous table access (requires the `tokio` feature).
+//!
+//! RocksDB calls block, so these methods run them on Tokio's blocking thread pool, and must be
+//! called from within a Tokio runtime.
+
+use crate::{mode, Entry, Table};
+use futures_core::Stream;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+/// The number of entries an iteration task may read ahead of its stream's consumer.
+const ITER_BUFFER_SIZE: usize = 256;
+
+/// A wrapper for a table that provides asynchronous versions of its methods.
+///
+/// The wrapper is cheap to clone, and clones share the underlying table.
+pub struct AsyncTable<M, T> {
+    table: Arc<T>,
+    _mode: PhantomData<M>,
+}
+
+impl<M, T> Clone for AsyncTable<M, T> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<M: mode::Mode + 'static, T: Table<M> + Send + Sync + 'static> AsyncTable<M, T>
+where
+    T::Key: Send + 'static,
+    T::Value: Send + 'static,
+    T::Error: Send + 'static,
+{
+    pub fn new(table: T) -> Self {
+        Self {
+            table: Arc::new(table),
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub async fn lookup_key(&self, key: T::Key) -> Result<Option<T::Value>, T::Error> {
+        self.run(move |table| table.lookup_key(&key)).await
+    }
+
+    pub async fn multi_lookup(&self, keys: Vec<T::Key>) -> Result<Vec<Option<T::Value>>, T::Error> {
+        self.run(move |table| table.multi_lookup(&keys)).await
+    }
+
+    pub async fn put(&self, key: T::Key, value: T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.run(move |table| table.put(&key, &value)).await
+    }
+
+    /// Iterate over the table, reading entries on a blocking thread.
+    ///
+    /// The iteration stops early if the stream is dropped.
+    pub fn iter(&self) -> AsyncTableIterator<M, T> {
+        let table = self.table.clone();
+        let (sender, receiver) = mpsc::channel(ITER_BUFFER_SIZE);
+
+        tokio::task::spawn_blocking(move || {
+            for result in table.iter() {
+                if sender.blocking_send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        AsyncTableIterator { receiver }
+    }
+
+    async fn run<F: FnOnce(&T) -> R + Send + 'static, R: Send + 'static>(&self, f: F) -> R {
+        let table = self.table.clone();
+
+        tokio::task::spawn_blocking(move || f(&table))
+            .await
+            .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
+    }
+}
+
+/// A stream of entries from an asynchronous table.
+pub struct AsyncTableIterator<M, T: Table<M>> {
+    receiver: mpsc::Receiver<Result<Entry<M, T>, T::Error>>,
+}
+
+impl<M, T: Table<M>> Stream for AsyncTableIterator<M, T> {
+    type Item = Result<Entry<M, T>, T::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
@@ -0,0 +1,292 @@
+//! Audit logs that record every change made to a table.
+
+use crate::{codec::ByteReader, error, mode, Table};
+use rocksdb::{Options, WriteBatch, DB};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The column family that stores audit records (keyed by big-endian sequence numbers).
+pub const AUDIT_COLUMN_FAMILY_NAME: &str = "__rocksdb_table_audit";
+
+/// The length prefix used for missing values in encoded records.
+const ABSENT: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Put,
+    Merge,
+    Delete,
+}
+
+impl Operation {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Put => 0,
+            Self::Merge => 1,
+            Self::Delete => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Put),
+            1 => Some(Self::Merge),
+            2 => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// A change made to a table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditRecord<K, V> {
+    pub sequence: u64,
+    pub timestamp: SystemTime,
+    pub operation: Operation,
+    pub key: K,
+    /// The value written (or the merge operand), if any.
+    pub value: Option<V>,
+    /// The value before the change, if there was one.
+    pub old_value: Option<V>,
+}
+
+/// Create the audit column family for a newly opened database if the table is audited.
+pub(crate) fn prepare<M: mode::Mode, T: Table<M>>(db: &mut DB) -> Result<(), error::Error> {
+    if M::is_primary() && T::audited() && db.cf_handle(AUDIT_COLUMN_FAMILY_NAME).is_none() {
+        db.create_cf(AUDIT_COLUMN_FAMILY_NAME, &Options::default())?;
+    }
+
+    Ok(())
+}
+
+/// A wrapper for a table that records every change made through it in the audit column family,
+/// in the same batch as the change itself.
+///
+/// The table must enable [`Table::audited`], so that the column family is created when the table
+/// is opened. Changes made through the underlying table (or any other handle to the same database)
+/// aren't recorded.
+pub struct AuditedTable<M, T> {
+    table: T,
+    next_sequence: Mutex<u64>,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::Mode, T: Table<M>> AuditedTable<M, T> {
+    pub fn new(table: T) -> Result<Self, T::Error> {
+        let db = &table.database().db;
+        let audit_cf = db.cf_handle(AUDIT_COLUMN_FAMILY_NAME).ok_or_else(|| {
+            error::Error::InvalidConfiguration("Missing audit column family".to_string())
+        })?;
+
+        let mut underlying = db.raw_iterator_cf(audit_cf);
+        underlying.seek_to_last();
+
+        let next_sequence = match underlying.key() {
+            Some(key_bytes) => ByteReader::for_key(key_bytes).read_u64_be()? + 1,
+            None => 0,
+        };
+
+        underlying.status().map_err(error::Error::from)?;
+        drop(underlying);
+
+        Ok(Self {
+            table,
+            next_sequence: Mutex::new(next_sequence),
+            _mode: PhantomData,
+        })
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub fn put(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let value_bytes = T::value_to_bytes(value)?;
+
+        self.write(
+            Operation::Put,
+            key,
+            Some(value_bytes.as_ref()),
+            |batch, key_bytes| batch.put(key_bytes, &value_bytes),
+        )
+    }
+
+    pub fn merge(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let value_bytes = T::value_to_bytes(value)?;
+
+        self.write(
+            Operation::Merge,
+            key,
+            Some(value_bytes.as_ref()),
+            |batch, key_bytes| batch.merge(key_bytes, &value_bytes),
+        )
+    }
+
+    pub fn delete(&self, key: &T::Key) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.write(Operation::Delete, key, None, |batch, key_bytes| {
+            batch.delete(key_bytes)
+        })
+    }
+
+    /// Iterate over the audit records in order, starting from the given sequence number.
+    pub fn records_from(&self, sequence: u64) -> AuditIterator<'_, M, T> {
+        let db = &self.table.database().db;
+        // The column family always exists, since it's checked when the wrapper is created.
+        let underlying = db.cf_handle(AUDIT_COLUMN_FAMILY_NAME).map(|audit_cf| {
+            let mut underlying = db.raw_iterator_cf(audit_cf);
+            underlying.seek(sequence.to_be_bytes());
+            underlying
+        });
+
+        AuditIterator {
+            underlying,
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }
+
+    /// Iterate over every audit record in order.
+    pub fn records(&self) -> AuditIterator<'_, M, T> {
+        self.records_from(0)
+    }
+
+    fn write<F: FnOnce(&mut WriteBatch, &[u8])>(
+        &self,
+        operation: Operation,
+        key: &T::Key,
+        value_bytes: Option<&[u8]>,
+        change: F,
+    ) -> Result<(), T::Error> {
+        let db = &self.table.database().db;
+        let audit_cf = db.cf_handle(AUDIT_COLUMN_FAMILY_NAME).ok_or_else(|| {
+            error::Error::InvalidConfiguration("Missing audit column family".to_string())
+        })?;
+        let key_bytes = T::key_to_bytes(key)?;
+        let key_bytes = key_bytes.as_ref();
+
+        // The lock is held until the batch is written, so that records are in sequence order and
+        // old values are accurate for changes made through this wrapper.
+        let mut next_sequence = self.next_sequence();
+        let old_value_bytes = db.get_pinned(key_bytes).map_err(error::Error::from)?;
+
+        let mut record = vec![];
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        record.extend_from_slice(&timestamp.to_be_bytes());
+        record.push(operation.to_byte());
+        encode_part(&mut record, Some(key_bytes));
+        encode_part(&mut record, value_bytes);
+        encode_part(&mut record, old_value_bytes.as_deref());
+
+        let mut batch = WriteBatch::default();
+        change(&mut batch, key_bytes);
+        batch.put_cf(audit_cf, next_sequence.to_be_bytes(), record);
+        db.write(batch).map_err(error::Error::from)?;
+
+        *next_sequence += 1;
+
+        Ok(())
+    }
+
+    fn next_sequence(&self) -> MutexGuard<'_, u64> {
+        // The sequence number is only incremented after a successful write, so a poisoned lock can
+        // still be used.
+        self.next_sequence
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn encode_part(record: &mut Vec<u8>, bytes: Option<&[u8]>) {
+    match bytes {
+        Some(bytes) => {
+            record.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            record.extend_from_slice(bytes);
+        }
+        None => record.extend_from_slice(&ABSENT.to_be_bytes()),
+    }
+}
+
+fn decode_part<'a>(reader: &mut ByteReader<'a>) -> Result<Option<&'a [u8]>, error::Error> {
+    match reader.read_u32_be()? {
+        ABSENT => Ok(None),
+        len => Ok(Some(reader.read_bytes(len as usize)?)),
+    }
+}
+
+fn decode_record<M, T: Table<M>>(
+    key_bytes: &[u8],
+    record_bytes: &[u8],
+) -> Result<AuditRecord<T::Key, T::Value>, T::Error> {
+    let sequence = ByteReader::for_key(key_bytes).read_u64_be()?;
+    let mut reader = ByteReader::for_value(record_bytes);
+    let timestamp = UNIX_EPOCH + Duration::from_micros(reader.read_u64_be()?);
+    let operation = Operation::from_byte(reader.read_u8()?).ok_or_else(|| reader.error())?;
+    let key_bytes = decode_part(&mut reader)?.ok_or_else(|| reader.error())?;
+    let value_bytes = decode_part(&mut reader)?;
+    let old_value_bytes = decode_part(&mut reader)?;
+    reader.finish()?;
+
+    let decode_value = |bytes: Option<&[u8]>| {
+        bytes
+            .map(|bytes| T::bytes_to_value(Cow::from(bytes)))
+            .transpose()
+    };
+
+    Ok(AuditRecord {
+        sequence,
+        timestamp,
+        operation,
+        key: T::bytes_to_key(Cow::from(key_bytes))?,
+        value: decode_value(value_bytes)?,
+        old_value: decode_value(old_value_bytes)?,
+    })
+}
+
+/// Iterates over audit records in sequence order.
+pub struct AuditIterator<'a, M, T> {
+    // This is `None` after the iterator fails or is exhausted.
+    underlying: Option<rocksdb::DBRawIterator<'a>>,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<M, T: Table<M>> Iterator for AuditIterator<'_, M, T> {
+    type Item = Result<AuditRecord<T::Key, T::Value>, T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let underlying = self.underlying.as_mut()?;
+
+        if let (Some(key_bytes), Some(record_bytes)) = (underlying.key(), underlying.value()) {
+            let result = decode_record::<M, T>(key_bytes, record_bytes);
+
+            if result.is_err() {
+                self.underlying = None;
+            } else {
+                underlying.next();
+            }
+
+            Some(result)
+        } else {
+            let status = underlying.status();
+            self.underlying = None;
+
+            status
+                .err()
+                .map(|error| Err(error::Error::from(error).into()))
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Block-based table configuration (filters, block size, and index type).
+
+use rocksdb::{BlockBasedIndexType, BlockBasedOptions, Cache, Options};
+
+/// A filter that allows lookups to skip blocks that don't contain a key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Bloom {
+        bits_per_key: f64,
+    },
+    /// A ribbon filter, which uses around 30% less memory than a Bloom filter with the same
+    /// false positive rate (but is more expensive to build).
+    Ribbon {
+        bloom_equivalent_bits_per_key: f64,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexType {
+    BinarySearch,
+    /// A hash index for prefix lookups (this requires a prefix extractor).
+    HashSearch,
+    /// A partitioned index (filters are also partitioned), which keeps most of the index out of
+    /// memory for very large tables.
+    TwoLevel,
+}
+
+/// Configuration for a table's block-based table options.
+#[derive(Clone)]
+pub struct BlockConfig {
+    pub block_size: usize,
+    pub filter: Option<Filter>,
+    /// Whether filters include whole keys (and not only prefixes).
+    pub whole_key_filtering: bool,
+    pub index_type: IndexType,
+    /// A block cache (for example one shared with [`crate::memory::MemoryBudget`]).
+    pub cache: Option<Cache>,
+}
+
+impl BlockConfig {
+    /// Configuration for tables that are mostly read by exact key lookups, with a Bloom filter.
+    pub fn point_lookup() -> Self {
+        Self {
+            filter: Some(Filter::Bloom { bits_per_key: 10.0 }),
+            ..Self::default()
+        }
+    }
+
+    pub fn block_options(&self) -> BlockBasedOptions {
+        let mut block_options = BlockBasedOptions::default();
+        block_options.set_block_size(self.block_size);
+        block_options.set_whole_key_filtering(self.whole_key_filtering);
+
+        match self.filter {
+            Some(Filter::Bloom { bits_per_key }) => {
+                block_options.set_bloom_filter(bits_per_key, false);
+            }
+            Some(Filter::Ribbon {
+                bloom_equivalent_bits_per_key,
+            }) => {
+                block_options.set_ribbon_filter(bloom_equivalent_bits_per_key);
+            }
+            None => {}
+        }
+
+        match self.index_type {
+            IndexType::BinarySearch => {
+                block_options.set_index_type(BlockBasedIndexType::BinarySearch);
+            }
+            IndexType::HashSearch => {
+                block_options.set_index_type(BlockBasedIndexType::HashSearch);
+            }
+            IndexType::TwoLevel => {
+                block_options.set_index_type(BlockBasedIndexType::TwoLevelIndexSearch);
+                block_options.set_partition_filters(self.filter.is_some());
+            }
+        }
+
+        if let Some(cache) = &self.cache {
+            block_options.set_block_cache(cache);
+            block_options.set_cache_index_and_filter_blocks(true);
+        }
+
+        block_options
+    }
+
+    /// Install these block options on the given options (replacing any existing block options).
+    pub fn configure(&self, options: &mut Options) {
+        options.set_block_based_table_factory(&self.block_options());
+    }
+}
+
+impl Default for BlockConfig {
+    fn default() -> Self {
+        Self {
+            block_size: 4 * 1024,
+            filter: None,
+            whole_key_filtering: true,
+            index_type: IndexType::BinarySearch,
+            cache: None,
+        }
+    }
+}
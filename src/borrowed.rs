@@ -0,0 +1,90 @@
+//! Decoding of keys and values as views borrowed from RocksDB's buffers.
+
+use crate::{mode, Table};
+use rocksdb::DBRawIterator;
+use std::marker::PhantomData;
+
+/// A borrowed key and value.
+pub type EntryRef<'a, M, T> = (
+    <T as TableRef<M>>::KeyRef<'a>,
+    <T as TableRef<M>>::ValueRef<'a>,
+);
+
+/// A table whose keys and values can be decoded without copying or allocating.
+///
+/// For tables with string or byte-array values, this avoids the allocations required by
+/// `bytes_to_key` and `bytes_to_value` for each entry during a scan.
+pub trait TableRef<M>: Table<M> {
+    type KeyRef<'a>;
+    type ValueRef<'a>;
+
+    fn bytes_to_key_ref(bytes: &[u8]) -> Result<Self::KeyRef<'_>, Self::Error>;
+    fn bytes_to_value_ref(bytes: &[u8]) -> Result<Self::ValueRef<'_>, Self::Error>;
+
+    /// Iterate over borrowed views of the table's entries.
+    fn iter_ref(&self) -> RefIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek_to_first();
+
+        RefIterator {
+            underlying,
+            started: false,
+            done: false,
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }
+}
+
+/// Iterates over borrowed entries, each of which is only valid until the next call to `next`.
+///
+/// Since its items borrow from the iterator, this can't implement [`Iterator`], and is used with
+/// `while let`:
+///
+/// ```ignore
+/// let mut entries = table.iter_ref();
+///
+/// while let Some(result) = entries.next() {
+///     let (key, value) = result?;
+/// }
+/// ```
+pub struct RefIterator<'a, M, T> {
+    underlying: DBRawIterator<'a>,
+    started: bool,
+    done: bool,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: TableRef<M>> RefIterator<'a, M, T> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<EntryRef<'_, M, T>, T::Error>> {
+        if self.done {
+            return None;
+        }
+
+        if self.started {
+            self.underlying.next();
+        } else {
+            self.started = true;
+        }
+
+        match self.underlying.item() {
+            Some((key_bytes, value_bytes)) => Some(
+                T::bytes_to_key_ref(key_bytes)
+                    .and_then(|key| T::bytes_to_value_ref(value_bytes).map(|value| (key, value))),
+            ),
+            None => {
+                self.done = true;
+
+                self.underlying
+                    .status()
+                    .err()
+                    .map(|error| Err(T::Error::from(error.into())))
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! A builder for opening tables with adjusted options.
+
+use crate::{block, error::Error, memory, mode, open_default_cf, Database, Table};
+use rocksdb::Options;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builds the options for a table, starting from the table's defaults.
+///
+/// Settings are validated when the table is opened.
+pub struct TableBuilder<M, T> {
+    options: Options,
+    parallelism: Option<i32>,
+    write_buffer_size: Option<usize>,
+    wal_dir: Option<PathBuf>,
+    ttl: Option<Duration>,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<M: mode::Mode + mode::SinglePath, T: Table<M>> TableBuilder<M, T> {
+    pub(crate) fn new() -> Self {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        Self {
+            options: T::configure_options(options),
+            parallelism: None,
+            write_buffer_size: None,
+            wal_dir: None,
+            ttl: None,
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }
+
+    /// The number of background threads for flushes and compactions.
+    pub fn parallelism(mut self, threads: i32) -> Self {
+        self.parallelism = Some(threads);
+        self
+    }
+
+    pub fn write_buffer_size(mut self, bytes: usize) -> Self {
+        self.write_buffer_size = Some(bytes);
+        self
+    }
+
+    /// A separate directory for the write-ahead log (for example on a faster disk).
+    pub fn wal_dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.wal_dir = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set a time-to-live in place of the table's default (this can't be used in read-only mode,
+    /// where the default is ignored).
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn block_config(mut self, block_config: &block::BlockConfig) -> Self {
+        block_config.configure(&mut self.options);
+        self
+    }
+
+    pub fn memory_budget(mut self, memory_budget: &memory::MemoryBudget) -> Self {
+        self.options = memory_budget.configure()(self.options);
+        self
+    }
+
+    /// Adjust the options directly.
+    pub fn configure<F: FnOnce(&mut Options)>(mut self, configure: F) -> Self {
+        configure(&mut self.options);
+        self
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.parallelism.is_some_and(|threads| threads < 1) {
+            return Err(Error::InvalidConfiguration(
+                "Parallelism must be positive".to_string(),
+            ));
+        }
+
+        if self.write_buffer_size == Some(0) {
+            return Err(Error::InvalidConfiguration(
+                "Write buffer size must be positive".to_string(),
+            ));
+        }
+
+        if self.ttl.is_some() && M::is_read_only() {
+            return Err(Error::InvalidConfiguration(
+                "A time-to-live can't be used in read-only mode".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn open<P: AsRef<Path>>(mut self, path: P) -> Result<T, Error> {
+        self.validate()?;
+
+        if let Some(threads) = self.parallelism {
+            self.options.increase_parallelism(threads);
+        }
+
+        if let Some(bytes) = self.write_buffer_size {
+            self.options.set_write_buffer_size(bytes);
+        }
+
+        if let Some(wal_dir) = &self.wal_dir {
+            self.options.set_wal_dir(wal_dir);
+        }
+
+        let ttl = self.ttl.or_else(T::default_ttl);
+        let db = open_default_cf::<M, T, _>(&self.options, path, ttl)?;
+
+        Ok(T::from_database(Database {
+            db: Arc::new(db),
+            options: self.options,
+            _temporary_directory: None,
+            _mode: PhantomData,
+        }))
+    }
+}
@@ -0,0 +1,292 @@
+//! An in-process cache of decoded values (requires the `lru` feature).
+
+use crate::{error, mode, Table};
+use lru::LruCache;
+use rocksdb::WriteBatch;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Configuration for buffering writes in memory before they're written to the database.
+///
+/// Buffered writes are visible to lookups through the same handle immediately, but they aren't
+/// durable until they're flushed, and are lost if the process crashes. Each flush is a single
+/// atomic batch, so after a crash the database always contains some prefix of the writes made
+/// through the handle. If a flush fails, the writes in it are discarded.
+///
+/// Buffered writes are flushed when their encoded size reaches `max_pending_bytes`, or when a
+/// write is made more than `max_delay` after the oldest buffered write (there's no background
+/// timer, so an idle handle keeps its buffer until [`CachedTable::flush`] is called or the handle
+/// is dropped).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WriteBehind {
+    pub max_pending_bytes: usize,
+    pub max_delay: Duration,
+}
+
+impl Default for WriteBehind {
+    fn default() -> Self {
+        Self {
+            max_pending_bytes: 4 * 1024 * 1024,
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+enum PendingValue<V> {
+    Put(V),
+    Deleted,
+    // The result of a merge is only known after it's been written.
+    Merged,
+}
+
+struct CacheState<V> {
+    values: LruCache<Vec<u8>, V>,
+    // Incremented on every write, so that lookups can avoid caching values read before a write.
+    generation: u64,
+    batch: WriteBatch,
+    pending: HashMap<Vec<u8>, PendingValue<V>>,
+    pending_since: Option<Instant>,
+}
+
+/// A wrapper for a table that keeps recently looked-up values in a size-bounded LRU cache.
+///
+/// Writes through this wrapper invalidate the cached values for their keys, but writes through
+/// the underlying table (or any other handle to the same database) don't, and may leave stale
+/// values in the cache.
+pub struct CachedTable<M, T: Table<M>> {
+    table: T,
+    state: Mutex<CacheState<T::Value>>,
+    write_behind: Option<WriteBehind>,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::Mode, T: Table<M>> CachedTable<M, T>
+where
+    T::Value: Clone,
+{
+    /// Wrap a table, caching at most `capacity` values.
+    pub fn new(table: T, capacity: NonZeroUsize) -> Self {
+        Self::create(table, capacity, None)
+    }
+
+    /// Wrap a table, caching at most `capacity` values and buffering writes.
+    pub fn with_write_behind(table: T, capacity: NonZeroUsize, write_behind: WriteBehind) -> Self
+    where
+        M: mode::IsWriteable,
+    {
+        Self::create(table, capacity, Some(write_behind))
+    }
+
+    fn create(table: T, capacity: NonZeroUsize, write_behind: Option<WriteBehind>) -> Self {
+        Self {
+            table,
+            state: Mutex::new(CacheState {
+                values: LruCache::new(capacity),
+                generation: 0,
+                batch: WriteBatch::default(),
+                pending: HashMap::new(),
+                pending_since: None,
+            }),
+            write_behind,
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub fn write_behind(&self) -> Option<WriteBehind> {
+        self.write_behind
+    }
+
+    /// The number of values currently cached (not including buffered writes).
+    pub fn cached_len(&self) -> usize {
+        self.state().values.len()
+    }
+
+    /// The number of keys with buffered writes.
+    pub fn pending_len(&self) -> usize {
+        self.state().pending.len()
+    }
+
+    pub fn clear_cache(&self) {
+        let mut state = self.state();
+        state.values.clear();
+        state.generation += 1;
+    }
+
+    pub fn lookup_key(&self, key: &T::Key) -> Result<Option<T::Value>, T::Error> {
+        let key_bytes = T::key_to_bytes(key)?;
+        let key_bytes = key_bytes.as_ref();
+
+        let generation = {
+            let mut state = self.state();
+
+            match state.pending.get(key_bytes) {
+                Some(PendingValue::Put(value)) => return Ok(Some(value.clone())),
+                Some(PendingValue::Deleted) => return Ok(None),
+                Some(PendingValue::Merged) => self.flush_pending(&mut state)?,
+                None => {}
+            }
+
+            if let Some(value) = state.values.get(key_bytes) {
+                return Ok(Some(value.clone()));
+            }
+
+            state.generation
+        };
+
+        let value = self.table.lookup_key(key)?;
+
+        if let Some(value) = &value {
+            let mut state = self.state();
+
+            if state.generation == generation {
+                state.values.put(key_bytes.to_vec(), value.clone());
+            }
+        }
+
+        Ok(value)
+    }
+
+    pub fn put(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        if self.write_behind.is_some() {
+            let key_bytes = T::key_to_bytes(key)?;
+            let value_bytes = T::value_to_bytes(value)?;
+
+            self.buffer(
+                key_bytes.as_ref(),
+                PendingValue::Put(value.clone()),
+                |batch| batch.put(key_bytes.as_ref(), value_bytes),
+            )
+        } else {
+            self.table.put(key, value)?;
+            self.invalidate(key)
+        }
+    }
+
+    pub fn merge(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        if self.write_behind.is_some() {
+            let key_bytes = T::key_to_bytes(key)?;
+            let value_bytes = T::value_to_bytes(value)?;
+
+            self.buffer(key_bytes.as_ref(), PendingValue::Merged, |batch| {
+                batch.merge(key_bytes.as_ref(), value_bytes)
+            })
+        } else {
+            self.table.merge(key, value)?;
+            self.invalidate(key)
+        }
+    }
+
+    pub fn delete(&self, key: &T::Key) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        if self.write_behind.is_some() {
+            let key_bytes = T::key_to_bytes(key)?;
+
+            self.buffer(key_bytes.as_ref(), PendingValue::Deleted, |batch| {
+                batch.delete(key_bytes.as_ref())
+            })
+        } else {
+            self.table.delete(key)?;
+            self.invalidate(key)
+        }
+    }
+
+    /// Write any buffered writes to the database.
+    pub fn flush(&self) -> Result<(), T::Error> {
+        Ok(self.flush_pending(&mut self.state())?)
+    }
+
+    fn buffer<F: FnOnce(&mut WriteBatch)>(
+        &self,
+        key_bytes: &[u8],
+        pending_value: PendingValue<T::Value>,
+        write: F,
+    ) -> Result<(), T::Error> {
+        let mut state = self.state();
+        write(&mut state.batch);
+
+        state.pending.insert(key_bytes.to_vec(), pending_value);
+        state.values.pop(key_bytes);
+        state.generation += 1;
+
+        let now = Instant::now();
+        let pending_since = *state.pending_since.get_or_insert(now);
+
+        if let Some(write_behind) = self.write_behind {
+            if state.batch.size_in_bytes() >= write_behind.max_pending_bytes
+                || now.duration_since(pending_since) >= write_behind.max_delay
+            {
+                self.flush_pending(&mut state)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn flush_pending(&self, state: &mut CacheState<T::Value>) -> Result<(), error::Error> {
+        if state.pending_since.take().is_some() {
+            let batch = std::mem::take(&mut state.batch);
+            let pending = std::mem::take(&mut state.pending);
+
+            self.table.database().db.write(batch)?;
+
+            // Values that were put are now known to be current.
+            for (key_bytes, pending_value) in pending {
+                if let PendingValue::Put(value) = pending_value {
+                    state.values.put(key_bytes, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn invalidate(&self, key: &T::Key) -> Result<(), T::Error> {
+        let key_bytes = T::key_to_bytes(key)?;
+        let mut state = self.state();
+        state.values.pop(key_bytes.as_ref());
+        state.generation += 1;
+
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState<T::Value>> {
+        lock(&self.state)
+    }
+}
+
+impl<M, T: Table<M>> Drop for CachedTable<M, T> {
+    /// Buffered writes are flushed on drop, but any error is ignored (use `flush` to handle it).
+    fn drop(&mut self) {
+        let mut state = lock(&self.state);
+
+        if state.pending_since.take().is_some() {
+            let _ = self
+                .table
+                .database()
+                .db
+                .write(std::mem::take(&mut state.batch));
+        }
+    }
+}
+
+fn lock<V>(state: &Mutex<CacheState<V>>) -> MutexGuard<'_, CacheState<V>> {
+    // The cache is never left inconsistent, so a poisoned lock can still be used.
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
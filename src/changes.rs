@@ -0,0 +1,141 @@
+//! Incremental exports of changed entries, based on sequence numbers.
+//!
+//! The stream starts with a magic number, a format version, and the sequence number that the
+//! export is complete up to. It's followed by a record for each changed key (a tag byte followed
+//! by the length-prefixed key, and for puts the length-prefixed value), and an end tag.
+
+use crate::{error::Error, mode, wal, Table, PUT_ALL_BATCH_SIZE};
+use rocksdb::WriteBatch;
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 4] = b"RTCH";
+const FORMAT_VERSION: u8 = 1;
+
+const PUT_TAG: u8 = 0;
+const DELETE_TAG: u8 = 1;
+const END_TAG: u8 = 2;
+
+/// A summary of an export or import.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ChangeSummary {
+    /// The sequence number that the changes are complete up to (which should be used for the next
+    /// export).
+    pub sequence: u64,
+    pub puts: u64,
+    pub deletes: u64,
+}
+
+/// Write the current values of the keys changed after the given sequence number.
+pub(crate) fn export_since<M, T: Table<M>, W: Write>(
+    table: &T,
+    sequence: u64,
+    mut writer: W,
+) -> Result<ChangeSummary, Error> {
+    let db = &table.database().db;
+    // Values are read after the log, so they may include later changes, which are exported again
+    // next time (applying a change twice has no effect).
+    let mut summary = ChangeSummary {
+        sequence: db.latest_sequence_number(),
+        ..ChangeSummary::default()
+    };
+    let keys = wal::changed_keys(db, sequence, summary.sequence)?;
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION])?;
+    writer.write_all(&summary.sequence.to_be_bytes())?;
+
+    for key_bytes in keys {
+        match db.get_pinned(&key_bytes)? {
+            Some(value_bytes) => {
+                writer.write_all(&[PUT_TAG])?;
+                write_part(&mut writer, &key_bytes)?;
+                write_part(&mut writer, &value_bytes)?;
+                summary.puts += 1;
+            }
+            None => {
+                writer.write_all(&[DELETE_TAG])?;
+                write_part(&mut writer, &key_bytes)?;
+                summary.deletes += 1;
+            }
+        }
+    }
+
+    writer.write_all(&[END_TAG])?;
+    writer.flush()?;
+
+    Ok(summary)
+}
+
+/// Apply changes written by [`export_since`], in batches.
+pub(crate) fn import_changes<M: mode::IsWriteable, T: Table<M>, R: Read>(
+    table: &T,
+    mut reader: R,
+) -> Result<ChangeSummary, Error> {
+    let db = &table.database().db;
+
+    let mut header = [0; 13];
+    reader.read_exact(&mut header)?;
+
+    if &header[0..4] != MAGIC || header[4] != FORMAT_VERSION {
+        return Err(Error::InvalidValue(header.to_vec()));
+    }
+
+    let mut summary = ChangeSummary {
+        sequence: u64::from_be_bytes(header[5..13].try_into().expect("Fixed-size header")),
+        ..ChangeSummary::default()
+    };
+    let mut batch = WriteBatch::default();
+
+    loop {
+        let mut tag = [0];
+        reader.read_exact(&mut tag)?;
+
+        match tag[0] {
+            PUT_TAG => {
+                let key_bytes = read_part(&mut reader)?;
+                let value_bytes = read_part(&mut reader)?;
+                batch.put(key_bytes, value_bytes);
+                summary.puts += 1;
+            }
+            DELETE_TAG => {
+                batch.delete(read_part(&mut reader)?);
+                summary.deletes += 1;
+            }
+            END_TAG => break,
+            _ => return Err(Error::InvalidValue(tag.to_vec())),
+        }
+
+        if batch.len() >= PUT_ALL_BATCH_SIZE {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    if !batch.is_empty() {
+        db.write(batch)?;
+    }
+
+    Ok(summary)
+}
+
+fn write_part<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), Error> {
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(bytes)?;
+
+    Ok(())
+}
+
+fn read_part<R: Read>(reader: &mut R) -> Result<Vec<u8>, Error> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+
+    // The length isn't trusted for allocation, since the stream may be truncated or corrupt.
+    let len = u64::from(u32::from_be_bytes(len));
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 == len {
+        Ok(bytes)
+    } else {
+        Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into())
+    }
+}
@@ -0,0 +1,210 @@
+//! Storage for values that are too large to store as single RocksDB values.
+
+use crate::{error, mode, Entry, Table};
+use rocksdb::{DBRawIterator, WriteBatch};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+const ORDINAL_LEN: usize = 4;
+
+/// A wrapper for a table that splits encoded values into chunks of at most `chunk_size` bytes.
+///
+/// Each chunk is stored under the encoded key followed by the chunk's big-endian `u32` ordinal,
+/// so the underlying table's own lookup and iteration methods shouldn't be used for chunked data.
+/// Key encodings must be fixed-length or self-delimiting (as with [`crate::codec::KeyCodec`]),
+/// since otherwise the chunks of different keys could be interleaved.
+pub struct ChunkedTable<M, T> {
+    table: T,
+    chunk_size: usize,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::Mode, T: Table<M>> ChunkedTable<M, T> {
+    /// Wrap a table, using the given maximum chunk size (which must be positive).
+    pub fn new(table: T, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "Chunk size must be positive");
+
+        Self {
+            table,
+            chunk_size,
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Replace the value for a key (any previous chunks are removed in the same write).
+    pub fn put(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let key_bytes = T::key_to_bytes(key)?;
+        let value_bytes = T::value_to_bytes(value)?;
+        let value_bytes = value_bytes.as_ref();
+
+        let mut batch = WriteBatch::default();
+        delete_chunks(&mut batch, key_bytes.as_ref());
+
+        // An empty value is still stored as a single empty chunk.
+        let chunks = value_bytes
+            .chunks(self.chunk_size)
+            .chain(value_bytes.is_empty().then_some(value_bytes));
+
+        for (ordinal, chunk) in chunks.enumerate() {
+            batch.put(chunk_key(key_bytes.as_ref(), ordinal as u32), chunk);
+        }
+
+        Ok(self
+            .table
+            .database()
+            .db
+            .write(batch)
+            .map_err(error::Error::from)?)
+    }
+
+    pub fn delete(&self, key: &T::Key) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let key_bytes = T::key_to_bytes(key)?;
+
+        let mut batch = WriteBatch::default();
+        delete_chunks(&mut batch, key_bytes.as_ref());
+
+        Ok(self
+            .table
+            .database()
+            .db
+            .write(batch)
+            .map_err(error::Error::from)?)
+    }
+
+    pub fn lookup_key(&self, key: &T::Key) -> Result<Option<T::Value>, T::Error> {
+        let key_bytes = T::key_to_bytes(key)?;
+        let key_bytes = key_bytes.as_ref();
+        let first_chunk_key = chunk_key(key_bytes, 0);
+
+        let mut underlying = self.table.database().db.raw_iterator();
+        underlying.seek(&first_chunk_key);
+
+        if underlying.key() == Some(first_chunk_key.as_slice()) {
+            let value_bytes = read_chunks(&mut underlying, key_bytes)?;
+
+            Ok(Some(T::bytes_to_value(Cow::from(value_bytes))?))
+        } else {
+            underlying.status().map_err(error::Error::from)?;
+
+            Ok(None)
+        }
+    }
+
+    pub fn iter(&self) -> ChunkedIterator<'_, M, T> {
+        let mut underlying = self.table.database().db.raw_iterator();
+        underlying.seek_to_first();
+
+        ChunkedIterator {
+            underlying,
+            done: false,
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }
+}
+
+fn chunk_key(key_bytes: &[u8], ordinal: u32) -> Vec<u8> {
+    let mut chunk_key = Vec::with_capacity(key_bytes.len() + ORDINAL_LEN);
+    chunk_key.extend_from_slice(key_bytes);
+    chunk_key.extend_from_slice(&ordinal.to_be_bytes());
+    chunk_key
+}
+
+fn delete_chunks(batch: &mut WriteBatch, key_bytes: &[u8]) {
+    // The last ordinal is never used, so the exclusive upper bound covers every chunk.
+    batch.delete_range(chunk_key(key_bytes, 0), chunk_key(key_bytes, u32::MAX));
+}
+
+/// Read the chunks for a key, starting from an iterator positioned at its first chunk.
+fn read_chunks(underlying: &mut DBRawIterator, key_bytes: &[u8]) -> Result<Vec<u8>, error::Error> {
+    let mut value_bytes = vec![];
+    let mut expected_ordinal = 0u32;
+
+    while let Some(chunk_key) = underlying.key() {
+        match chunk_key.strip_prefix(key_bytes) {
+            Some(ordinal_bytes) if ordinal_bytes.len() == ORDINAL_LEN => {
+                if ordinal_bytes != expected_ordinal.to_be_bytes() {
+                    return Err(error::Error::InvalidKey(chunk_key.to_vec()));
+                }
+            }
+            _ => break,
+        }
+
+        if let Some(chunk) = underlying.value() {
+            value_bytes.extend_from_slice(chunk);
+        }
+
+        expected_ordinal += 1;
+        underlying.next();
+    }
+
+    underlying.status()?;
+
+    Ok(value_bytes)
+}
+
+/// Iterates over reassembled entries in a chunked table.
+pub struct ChunkedIterator<'a, M, T> {
+    underlying: DBRawIterator<'a>,
+    done: bool,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: Table<M>> ChunkedIterator<'a, M, T> {
+    fn next_entry(&mut self) -> Result<Option<Entry<M, T>>, T::Error> {
+        let first_chunk_key = match self.underlying.key() {
+            Some(first_chunk_key) => first_chunk_key,
+            None => {
+                self.underlying.status().map_err(error::Error::from)?;
+                return Ok(None);
+            }
+        };
+
+        let key_bytes = first_chunk_key
+            .len()
+            .checked_sub(ORDINAL_LEN)
+            .map(|key_len| first_chunk_key[..key_len].to_vec())
+            .ok_or_else(|| error::Error::InvalidKey(first_chunk_key.to_vec()))?;
+
+        let value_bytes = read_chunks(&mut self.underlying, &key_bytes)?;
+
+        Ok(Some((
+            T::bytes_to_key(Cow::from(key_bytes))?,
+            T::bytes_to_value(Cow::from(value_bytes))?,
+        )))
+    }
+}
+
+impl<'a, M: mode::Mode, T: Table<M>> Iterator for ChunkedIterator<'a, M, T> {
+    type Item = Result<(T::Key, T::Value), T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            let result = self.next_entry();
+
+            // Stop after the end or the first error (which would otherwise repeat).
+            if !matches!(result, Ok(Some(_))) {
+                self.done = true;
+            }
+
+            result.transpose()
+        }
+    }
+}
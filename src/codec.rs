@@ -0,0 +1,425 @@
+//! Order-preserving encodings for key components.
+//!
+//! For every type here, comparing encoded bytes gives the same result as comparing the original
+//! values (using [`f64::total_cmp`] for floats), so tables that use these encodings iterate in
+//! key order. Encodings are either fixed-width or self-delimiting, which means they can be
+//! concatenated to build composite keys.
+
+use crate::error::Error;
+
+mod reader;
+pub mod varint;
+
+pub use reader::ByteReader;
+
+macro_rules! unsigned_codec {
+    ($t:ty, $n:literal, $encode:ident, $decode:ident) => {
+        #[doc = concat!("Encode a `", stringify!($t), "` as big-endian bytes.")]
+        pub fn $encode(value: $t) -> [u8; $n] {
+            value.to_be_bytes()
+        }
+
+        #[doc = concat!("Decode a `", stringify!($t), "` from big-endian bytes.")]
+        pub fn $decode(bytes: [u8; $n]) -> $t {
+            <$t>::from_be_bytes(bytes)
+        }
+    };
+}
+
+macro_rules! signed_codec {
+    ($t:ty, $u:ty, $n:literal, $encode:ident, $decode:ident) => {
+        #[doc = concat!(
+            "Encode an `", stringify!($t), "` as big-endian bytes with the sign bit flipped (so ",
+            "that negative values sort before positive ones)."
+        )]
+        pub fn $encode(value: $t) -> [u8; $n] {
+            ((value as $u) ^ (1 << ($n * 8 - 1))).to_be_bytes()
+        }
+
+        #[doc = concat!(
+            "Decode an `", stringify!($t), "` encoded with [`", stringify!($encode), "`]."
+        )]
+        pub fn $decode(bytes: [u8; $n]) -> $t {
+            (<$u>::from_be_bytes(bytes) ^ (1 << ($n * 8 - 1))) as $t
+        }
+    };
+}
+
+unsigned_codec!(u16, 2, encode_u16, decode_u16);
+unsigned_codec!(u32, 4, encode_u32, decode_u32);
+unsigned_codec!(u64, 8, encode_u64, decode_u64);
+unsigned_codec!(u128, 16, encode_u128, decode_u128);
+
+signed_codec!(i16, u16, 2, encode_i16, decode_i16);
+signed_codec!(i32, u32, 4, encode_i32, decode_i32);
+signed_codec!(i64, u64, 8, encode_i64, decode_i64);
+signed_codec!(i128, u128, 16, encode_i128, decode_i128);
+
+const F64_SIGN: u64 = 1 << 63;
+
+/// Encode an `f64` so that byte order matches [`f64::total_cmp`] (in which negative zero sorts
+/// before positive zero, and NaNs sort at the ends according to their sign).
+pub fn encode_f64(value: f64) -> [u8; 8] {
+    let bits = value.to_bits();
+
+    if bits & F64_SIGN == 0 {
+        bits ^ F64_SIGN
+    } else {
+        !bits
+    }
+    .to_be_bytes()
+}
+
+/// Decode an `f64` encoded with [`encode_f64`].
+pub fn decode_f64(bytes: [u8; 8]) -> f64 {
+    let bits = u64::from_be_bytes(bytes);
+
+    f64::from_bits(if bits & F64_SIGN == 0 {
+        !bits
+    } else {
+        bits ^ F64_SIGN
+    })
+}
+
+/// Append a self-delimiting encoding of a string to a buffer.
+///
+/// Zero bytes are escaped as `0x00 0xFF`, and the string is terminated by `0x00 0x00`, so that
+/// a string sorts before any longer string that it's a prefix of, even when followed by other
+/// key components.
+pub fn encode_str_into(value: &str, buffer: &mut Vec<u8>) {
+    for byte in value.bytes() {
+        buffer.push(byte);
+
+        if byte == 0 {
+            buffer.push(0xff);
+        }
+    }
+
+    buffer.extend_from_slice(&[0, 0]);
+}
+
+/// Encode a string using [`encode_str_into`].
+pub fn encode_str(value: &str) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(value.len() + 2);
+    encode_str_into(value, &mut buffer);
+    buffer
+}
+
+/// Decode a string encoded with [`encode_str_into`] from the start of the given bytes, returning
+/// the string and the number of bytes consumed.
+pub fn decode_str(bytes: &[u8]) -> Result<(String, usize), Error> {
+    let invalid = || Error::InvalidKey(bytes.to_vec());
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    loop {
+        match bytes.get(index) {
+            Some(0) => match bytes.get(index + 1) {
+                Some(0) => break,
+                Some(0xff) => {
+                    result.push(0);
+                    index += 2;
+                }
+                _ => return Err(invalid()),
+            },
+            Some(byte) => {
+                result.push(*byte);
+                index += 1;
+            }
+            None => return Err(invalid()),
+        }
+    }
+
+    let value = String::from_utf8(result).map_err(|_| invalid())?;
+
+    Ok((value, index + 2))
+}
+
+/// Encode a UUID as its bytes (which matches the order of [`uuid::Uuid`]).
+#[cfg(feature = "uuid")]
+pub fn encode_uuid(value: &uuid::Uuid) -> [u8; 16] {
+    *value.as_bytes()
+}
+
+/// Decode a UUID encoded with [`encode_uuid`].
+#[cfg(feature = "uuid")]
+pub fn decode_uuid(bytes: [u8; 16]) -> uuid::Uuid {
+    uuid::Uuid::from_bytes(bytes)
+}
+
+/// Encode a UTC timestamp as its sign-flipped seconds since the epoch, followed by nanoseconds.
+#[cfg(feature = "chrono")]
+pub fn encode_date_time(value: &chrono::DateTime<chrono::Utc>) -> [u8; 12] {
+    let mut bytes = [0; 12];
+    bytes[0..8].copy_from_slice(&encode_i64(value.timestamp()));
+    bytes[8..12].copy_from_slice(&encode_u32(value.timestamp_subsec_nanos()));
+    bytes
+}
+
+/// Decode a UTC timestamp encoded with [`encode_date_time`].
+#[cfg(feature = "chrono")]
+pub fn decode_date_time(bytes: [u8; 12]) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+    let (seconds, nanoseconds) = bytes.split_at(8);
+
+    chrono::DateTime::from_timestamp(
+        decode_i64(seconds.try_into().expect("Split at fixed offset")),
+        decode_u32(nanoseconds.try_into().expect("Split at fixed offset")),
+    )
+    .ok_or_else(|| Error::InvalidKey(bytes.to_vec()))
+}
+
+/// A key component with an order-preserving encoding that can be concatenated with others.
+///
+/// Tuples of key codecs are also key codecs, so composite keys can be encoded and decoded in one
+/// line (for example `KeyCodec::encode(&(user_id, timestamp))`).
+pub trait KeyCodec: Sized {
+    /// Append the encoding of this value to a buffer.
+    fn encode_into(&self, buffer: &mut Vec<u8>);
+
+    /// Decode a value from the start of the given bytes, returning it with the remaining bytes.
+    fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error>;
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+        self.encode_into(&mut buffer);
+        buffer
+    }
+
+    /// Decode a value that must use all of the given bytes.
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let (value, remaining) = Self::decode_prefix(bytes)?;
+
+        if remaining.is_empty() {
+            Ok(value)
+        } else {
+            Err(Error::InvalidKey(bytes.to_vec()))
+        }
+    }
+}
+
+/// Split a fixed number of bytes from the start of a slice.
+fn split_fixed<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8]), Error> {
+    bytes
+        .split_first_chunk::<N>()
+        .map(|(prefix, remaining)| (*prefix, remaining))
+        .ok_or_else(|| Error::InvalidKey(bytes.to_vec()))
+}
+
+macro_rules! fixed_key_codec {
+    ($t:ty, $n:literal, $encode:ident, $decode:ident) => {
+        impl KeyCodec for $t {
+            fn encode_into(&self, buffer: &mut Vec<u8>) {
+                buffer.extend_from_slice(&$encode(*self));
+            }
+
+            fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+                let (prefix, remaining) = split_fixed::<$n>(bytes)?;
+
+                Ok(($decode(prefix), remaining))
+            }
+        }
+    };
+}
+
+fixed_key_codec!(u16, 2, encode_u16, decode_u16);
+fixed_key_codec!(u32, 4, encode_u32, decode_u32);
+fixed_key_codec!(u64, 8, encode_u64, decode_u64);
+fixed_key_codec!(u128, 16, encode_u128, decode_u128);
+fixed_key_codec!(i16, 2, encode_i16, decode_i16);
+fixed_key_codec!(i32, 4, encode_i32, decode_i32);
+fixed_key_codec!(i64, 8, encode_i64, decode_i64);
+fixed_key_codec!(i128, 16, encode_i128, decode_i128);
+fixed_key_codec!(f64, 8, encode_f64, decode_f64);
+
+impl KeyCodec for u8 {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.push(*self);
+    }
+
+    fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (prefix, remaining) = split_fixed::<1>(bytes)?;
+
+        Ok((prefix[0], remaining))
+    }
+}
+
+impl<const N: usize> KeyCodec for [u8; N] {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(self);
+    }
+
+    fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        split_fixed::<N>(bytes)
+    }
+}
+
+impl KeyCodec for String {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        encode_str_into(self, buffer);
+    }
+
+    fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (value, len) = decode_str(bytes)?;
+
+        Ok((value, &bytes[len..]))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl KeyCodec for uuid::Uuid {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&encode_uuid(self));
+    }
+
+    fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (prefix, remaining) = split_fixed::<16>(bytes)?;
+
+        Ok((decode_uuid(prefix), remaining))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl KeyCodec for chrono::DateTime<chrono::Utc> {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&encode_date_time(self));
+    }
+
+    fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (prefix, remaining) = split_fixed::<12>(bytes)?;
+
+        Ok((decode_date_time(prefix)?, remaining))
+    }
+}
+
+macro_rules! tuple_key_codec {
+    ($($t:ident),+) => {
+        impl<$($t: KeyCodec),+> KeyCodec for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn encode_into(&self, buffer: &mut Vec<u8>) {
+                let ($($t,)+) = self;
+                $($t.encode_into(buffer);)+
+            }
+
+            #[allow(non_snake_case)]
+            fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+                let remaining = bytes;
+                $(let ($t, remaining) = $t::decode_prefix(remaining)?;)+
+
+                Ok((($($t,)+), remaining))
+            }
+        }
+    };
+}
+
+tuple_key_codec!(A);
+tuple_key_codec!(A, B);
+tuple_key_codec!(A, B, C);
+tuple_key_codec!(A, B, C, D);
+tuple_key_codec!(A, B, C, D, E);
+tuple_key_codec!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T, B: Clone + Ord + std::fmt::Debug>(sorted: &[T], encode: impl Fn(&T) -> B) {
+        let encoded = sorted.iter().map(encode).collect::<Vec<_>>();
+        let mut resorted = encoded.clone();
+        resorted.sort();
+
+        assert_eq!(encoded, resorted);
+    }
+
+    #[test]
+    fn integers() {
+        let values = [i64::MIN, -1000, -1, 0, 1, 255, 256, i64::MAX];
+
+        check_order(&values, |value| encode_i64(*value));
+        check_order(&[0u32, 1, 255, 256, u32::MAX], |value| encode_u32(*value));
+
+        for value in values {
+            assert_eq!(decode_i64(encode_i64(value)), value);
+        }
+
+        assert_eq!(decode_i16(encode_i16(-3)), -3);
+    }
+
+    #[test]
+    fn floats() {
+        let values = [
+            f64::NEG_INFINITY,
+            -1.5,
+            -f64::MIN_POSITIVE,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            1.0,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+
+        check_order(&values, |value| encode_f64(*value));
+
+        for value in values {
+            assert_eq!(decode_f64(encode_f64(value)).to_bits(), value.to_bits());
+        }
+    }
+
+    #[test]
+    fn strings() {
+        let values = ["", "\0", "\0\0", "\0a", "a", "a\0", "ab", "b"];
+
+        check_order(&values, |value| encode_str(value));
+
+        for value in values {
+            let mut encoded = encode_str(value);
+            let len = encoded.len();
+            encoded.extend_from_slice(b"suffix");
+
+            assert_eq!(decode_str(&encoded).unwrap(), (value.to_string(), len));
+        }
+
+        assert!(decode_str(b"abc").is_err());
+        assert!(decode_str(&[0, 1]).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_times() {
+        let values = [
+            chrono::DateTime::from_timestamp(-100, 5).unwrap(),
+            chrono::DateTime::from_timestamp(0, 0).unwrap(),
+            chrono::DateTime::from_timestamp(0, 1).unwrap(),
+            chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        ];
+
+        check_order(&values, encode_date_time);
+
+        for value in values {
+            assert_eq!(decode_date_time(encode_date_time(&value)).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn tuples() {
+        let values = [
+            (1u16, "".to_string(), -1i32),
+            (1, "a".to_string(), -5),
+            (1, "a".to_string(), 3),
+            (1, "ab".to_string(), i32::MIN),
+            (2, "".to_string(), 0),
+        ];
+
+        check_order(&values, KeyCodec::encode);
+
+        for value in values {
+            assert_eq!(KeyCodec::decode(&value.encode()).ok(), Some(value));
+        }
+
+        let encoded = (1u16, 2u32).encode();
+
+        assert_eq!(encoded, vec![0, 1, 0, 0, 0, 2]);
+        assert!(<(u16, u32)>::decode(&encoded[..5]).is_err());
+        assert!(<(u16, u16)>::decode(&encoded).is_err());
+    }
+}
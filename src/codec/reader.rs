@@ -0,0 +1,157 @@
+use crate::error::Error;
+
+/// Whether the bytes being read are a key or a value (which determines the error returned).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Part {
+    Key,
+    Value,
+}
+
+/// A cursor for decoding keys and values without panicking on short or malformed input.
+///
+/// Every read checks bounds, and failures are reported as [`Error::InvalidKeyAt`] or
+/// [`Error::InvalidValueAt`] with the offset of the failed read.
+#[derive(Clone, Debug)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    part: Part,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn for_key(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            part: Part::Key,
+        }
+    }
+
+    pub fn for_value(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            part: Part::Value,
+        }
+    }
+
+    /// An error for the current offset.
+    pub fn error(&self) -> Error {
+        match self.part {
+            Part::Key => Error::InvalidKeyAt {
+                bytes: self.bytes.to_vec(),
+                offset: self.offset,
+            },
+            Part::Value => Error::InvalidValueAt {
+                bytes: self.bytes.to_vec(),
+                offset: self.offset,
+            },
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The bytes that haven't been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        match self.remaining().get(..len) {
+            Some(bytes) => {
+                self.offset += len;
+                Ok(bytes)
+            }
+            None => Err(self.error()),
+        }
+    }
+
+    pub fn read_fixed<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        match self.remaining().first_chunk::<N>() {
+            Some(bytes) => {
+                self.offset += N;
+                Ok(*bytes)
+            }
+            None => Err(self.error()),
+        }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_fixed::<1>()?[0])
+    }
+
+    pub fn read_u16_be(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.read_fixed()?))
+    }
+
+    pub fn read_u32_be(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.read_fixed()?))
+    }
+
+    pub fn read_u64_be(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_be_bytes(self.read_fixed()?))
+    }
+
+    /// Read a value with a [`super::KeyCodec`] encoding.
+    pub fn read_codec<T: super::KeyCodec>(&mut self) -> Result<T, Error> {
+        let remaining = self.remaining();
+        let (value, rest) = T::decode_prefix(remaining).map_err(|_| self.error())?;
+        self.offset += remaining.len() - rest.len();
+
+        Ok(value)
+    }
+
+    /// Read all remaining bytes.
+    pub fn read_rest(&mut self) -> &'a [u8] {
+        let rest = self.remaining();
+        self.offset = self.bytes.len();
+        rest
+    }
+
+    /// Check that all bytes have been read.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_reader() {
+        let bytes = [0, 1, 0, 0, 0, 2, b'a', 0, 0, 9];
+        let mut reader = ByteReader::for_key(&bytes);
+
+        assert_eq!(reader.read_u16_be().unwrap(), 1);
+        assert_eq!(reader.read_u32_be().unwrap(), 2);
+        assert_eq!(reader.read_codec::<String>().unwrap(), "a");
+        assert_eq!(reader.offset(), 9);
+        assert!(matches!(
+            reader.read_u16_be(),
+            Err(Error::InvalidKeyAt { offset: 9, .. })
+        ));
+        assert_eq!(reader.remaining(), &[9]);
+        assert!(matches!(
+            reader.clone().finish(),
+            Err(Error::InvalidKeyAt { offset: 9, .. })
+        ));
+        assert_eq!(reader.read_u8().unwrap(), 9);
+        assert!(reader.finish().is_ok());
+
+        assert!(matches!(
+            ByteReader::for_value(&[1, 2]).read_fixed::<3>(),
+            Err(Error::InvalidValueAt { offset: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! Compact variable-length encodings for values.
+//!
+//! Unlike the encodings in the parent module, these don't preserve order, so they shouldn't be
+//! used for keys. LEB128 varints take one byte for values below 128 and up to ten bytes for
+//! `u64::MAX`, so they're usually smaller than fixed-width encodings for counts, while delta
+//! encoding makes sorted sequences like timestamps much smaller. Run `cargo bench --bench varint`
+//! to compare sizes and speeds with fixed-width encoding.
+
+use crate::error::Error;
+
+const MAX_LEN: usize = 10;
+
+/// Append the LEB128 encoding of an unsigned integer to a buffer.
+pub fn encode_varint_into(mut value: u64, buffer: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+
+    buffer.push(value as u8);
+}
+
+/// Decode a LEB128 unsigned integer from the start of the given bytes, returning the value and
+/// the number of bytes consumed.
+pub fn decode_varint(bytes: &[u8]) -> Result<(u64, usize), Error> {
+    let mut value = 0u64;
+
+    for (index, byte) in bytes.iter().take(MAX_LEN).enumerate() {
+        let bits = u64::from(byte & 0x7f);
+
+        // The tenth byte can only contribute the highest bit.
+        if index == MAX_LEN - 1 && bits > 1 {
+            break;
+        }
+
+        value |= bits << (7 * index);
+
+        if byte & 0x80 == 0 {
+            return Ok((value, index + 1));
+        }
+    }
+
+    Err(Error::InvalidValue(bytes.to_vec()))
+}
+
+/// Append a zigzag LEB128 encoding of a signed integer (so that values near zero are short).
+pub fn encode_signed_varint_into(value: i64, buffer: &mut Vec<u8>) {
+    encode_varint_into(((value << 1) ^ (value >> 63)) as u64, buffer);
+}
+
+/// Decode a signed integer encoded with [`encode_signed_varint_into`].
+pub fn decode_signed_varint(bytes: &[u8]) -> Result<(i64, usize), Error> {
+    let (value, len) = decode_varint(bytes)?;
+
+    Ok((((value >> 1) as i64) ^ -((value & 1) as i64), len))
+}
+
+/// Encode a sequence of integers as varint differences between consecutive values.
+///
+/// Any sequence can be encoded, but the encoding is only compact for non-decreasing sequences
+/// with small gaps.
+pub fn encode_deltas(values: &[u64]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(values.len());
+    let mut previous = 0;
+
+    for value in values {
+        encode_varint_into(value.wrapping_sub(previous), &mut buffer);
+        previous = *value;
+    }
+
+    buffer
+}
+
+/// Decode a sequence of integers encoded with [`encode_deltas`].
+pub fn decode_deltas(bytes: &[u8]) -> Result<Vec<u64>, Error> {
+    let mut values = vec![];
+    let mut remaining = bytes;
+    let mut previous = 0u64;
+
+    while !remaining.is_empty() {
+        let (delta, len) =
+            decode_varint(remaining).map_err(|_| Error::InvalidValue(bytes.to_vec()))?;
+        previous = previous.wrapping_add(delta);
+        values.push(previous);
+        remaining = &remaining[len..];
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varints() {
+        for (value, len) in [(0, 1), (127, 1), (128, 2), (16_383, 2), (u64::MAX, 10)] {
+            let mut buffer = vec![];
+            encode_varint_into(value, &mut buffer);
+
+            assert_eq!(buffer.len(), len);
+            assert_eq!(decode_varint(&buffer).unwrap(), (value, len));
+        }
+
+        for value in [0, -1, 1, -64, 64, i64::MIN, i64::MAX] {
+            let mut buffer = vec![];
+            encode_signed_varint_into(value, &mut buffer);
+
+            assert_eq!(
+                decode_signed_varint(&buffer).unwrap(),
+                (value, buffer.len())
+            );
+        }
+
+        assert!(decode_varint(&[0x80]).is_err());
+        assert!(decode_varint(&[0xff; 10]).is_err());
+    }
+
+    #[test]
+    fn deltas() {
+        let timestamps = [1_700_000_000, 1_700_000_005, 1_700_000_060, 1_700_000_060];
+        let encoded = encode_deltas(&timestamps);
+
+        // Compared to 32 bytes for fixed-width encodings.
+        assert_eq!(encoded.len(), 8);
+        assert_eq!(decode_deltas(&encoded).unwrap(), timestamps);
+        assert_eq!(
+            decode_deltas(&encode_deltas(&[5, 3, 0])).unwrap(),
+            vec![5, 3, 0]
+        );
+        assert!(decode_deltas(&[0x80]).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Compaction filter configuration.
+
+use crate::Table;
+use rocksdb::compaction_filter::Decision;
+use rocksdb::Options;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// A byte-level compaction filter function, which receives the level, key, and value.
+pub type FilterFn = Arc<dyn Fn(u32, &[u8], &[u8]) -> FilterDecision + Send + Sync>;
+
+/// Whether an entry should be kept or removed during compaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterDecision {
+    Keep,
+    Remove,
+}
+
+/// A named compaction filter that can be installed on database options.
+#[derive(Clone)]
+pub struct CompactionFilter {
+    name: String,
+    filter: FilterFn,
+}
+
+impl CompactionFilter {
+    pub fn new<F>(name: &str, filter: F) -> Self
+    where
+        F: Fn(u32, &[u8], &[u8]) -> FilterDecision + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            filter: Arc::new(filter),
+        }
+    }
+
+    /// A compaction filter defined over decoded keys and values.
+    ///
+    /// Entries that can't be decoded are always kept.
+    pub fn typed<M: 'static, T: Table<M> + 'static, F>(name: &str, filter: F) -> Self
+    where
+        F: Fn(&T::Key, &T::Value) -> FilterDecision + Send + Sync + 'static,
+    {
+        Self::new(name, move |_level, key_bytes, value_bytes| {
+            match (
+                T::bytes_to_key(Cow::from(key_bytes)),
+                T::bytes_to_value(Cow::from(value_bytes)),
+            ) {
+                (Ok(key), Ok(value)) => filter(&key, &value),
+                _ => FilterDecision::Keep,
+            }
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Decide whether to keep an entry.
+    pub fn apply(&self, level: u32, key: &[u8], value: &[u8]) -> FilterDecision {
+        (self.filter)(level, key, value)
+    }
+
+    /// Install this compaction filter on the given options.
+    pub fn configure(&self, options: &mut Options) {
+        let filter = self.filter.clone();
+
+        options.set_compaction_filter(&self.name, move |level, key, value| {
+            match filter(level, key, value) {
+                FilterDecision::Keep => Decision::Keep,
+                FilterDecision::Remove => Decision::Remove,
+            }
+        });
+    }
+}
@@ -0,0 +1,94 @@
+//! Custom key comparator configuration.
+
+use crate::Table;
+use rocksdb::Options;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+/// A byte-level key comparison function.
+pub type CompareFn = Arc<dyn Fn(&[u8], &[u8]) -> Ordering + Send + Sync>;
+
+/// A named key comparator that can be installed on database options.
+///
+/// RocksDB requires a database to be opened with a comparator with the same name and behavior
+/// every time, so the name should change whenever the ordering does.
+#[derive(Clone)]
+pub struct Comparator {
+    name: String,
+    compare: CompareFn,
+}
+
+impl Comparator {
+    pub fn new<F>(name: &str, compare: F) -> Self
+    where
+        F: Fn(&[u8], &[u8]) -> Ordering + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            compare: Arc::new(compare),
+        }
+    }
+
+    /// A comparator defined over decoded keys.
+    ///
+    /// Keys that compare as equal are ordered by their bytes (so that distinct encodings are
+    /// never treated as the same key), and keys that can't be decoded are ordered by their bytes
+    /// after all valid keys.
+    pub fn typed<M: 'static, T: Table<M> + 'static, F>(name: &str, compare: F) -> Self
+    where
+        F: Fn(&T::Key, &T::Key) -> Ordering + Send + Sync + 'static,
+    {
+        Self::new(name, move |a, b| {
+            match (T::bytes_to_key(Cow::from(a)), T::bytes_to_key(Cow::from(b))) {
+                (Ok(key_a), Ok(key_b)) => compare(&key_a, &key_b).then_with(|| a.cmp(b)),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            }
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        (self.compare)(a, b)
+    }
+
+    /// Install this comparator on the given options.
+    pub fn configure(&self, options: &mut Options) {
+        let compare = self.compare.clone();
+
+        options.set_comparator(&self.name, Box::new(move |a, b| compare(a, b)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocksdb::DB;
+
+    #[test]
+    fn reverse() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        Comparator::new("reverse", |a, b| b.cmp(a)).configure(&mut options);
+
+        let db = DB::open(&options, directory.path()).unwrap();
+
+        for key in [b"a", b"c", b"b"] {
+            db.put(key, b"").unwrap();
+        }
+
+        let keys = db
+            .iterator(rocksdb::IteratorMode::Start)
+            .map(|result| result.map(|(key, _)| key.to_vec()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(keys, vec![b"c".to_vec(), b"b".to_vec(), b"a".to_vec()]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Zstandard compression for encoded values (requires the `zstd` feature).
+//!
+//! RocksDB already compresses data blocks, but small values often compress much better with a
+//! dictionary trained on samples of the table's values. A codec can be stored in a static and used
+//! from a table's value encoding methods:
+//!
+//! ```ignore
+//! static CODEC: OnceLock<ZstdCodec> = OnceLock::new();
+//!
+//! fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+//!     Ok(CODEC.get_or_init(load_codec).compress(&encode_value(value))?)
+//! }
+//! ```
+
+use crate::error::Error;
+use std::io::Read;
+use std::sync::Arc;
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+/// A Zstandard compression configuration, with an optional dictionary.
+#[derive(Clone, Debug)]
+pub struct ZstdCodec {
+    level: i32,
+    dictionary: Option<Vec<u8>>,
+    // Preparing a dictionary is expensive, so this is done once and shared by clones.
+    prepared: Option<Arc<PreparedDictionary>>,
+}
+
+impl PartialEq for ZstdCodec {
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level && self.dictionary == other.dictionary
+    }
+}
+
+impl Eq for ZstdCodec {}
+
+/// A dictionary that has been loaded for compression and decompression.
+struct PreparedDictionary {
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl std::fmt::Debug for PreparedDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreparedDictionary").finish_non_exhaustive()
+    }
+}
+
+impl ZstdCodec {
+    pub fn new(level: i32) -> Self {
+        Self {
+            level,
+            dictionary: None,
+            prepared: None,
+        }
+    }
+
+    /// A codec using a dictionary (which must be the same for compression and decompression).
+    pub fn with_dictionary(level: i32, dictionary: Vec<u8>) -> Self {
+        let prepared = PreparedDictionary {
+            encoder: EncoderDictionary::copy(&dictionary, level),
+            decoder: DecoderDictionary::copy(&dictionary),
+        };
+
+        Self {
+            level,
+            dictionary: Some(dictionary),
+            prepared: Some(Arc::new(prepared)),
+        }
+    }
+
+    /// Train a dictionary of at most `max_size` bytes on sample values.
+    ///
+    /// Training needs a reasonably large number of samples (hundreds or more), and the dictionary
+    /// should be stored with the table, since values can't be decompressed without it.
+    pub fn train_dictionary<S: AsRef<[u8]>>(
+        samples: &[S],
+        max_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        Ok(zstd::dict::from_samples(samples, max_size)?)
+    }
+
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    pub fn dictionary(&self) -> Option<&[u8]> {
+        self.dictionary.as_deref()
+    }
+
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut compressor = match &self.prepared {
+            Some(prepared) => zstd::bulk::Compressor::with_prepared_dictionary(&prepared.encoder),
+            None => zstd::bulk::Compressor::new(self.level),
+        }
+        .map_err(|error| Error::ValueEncoding(error.to_string()))?;
+
+        compressor
+            .compress(bytes)
+            .map_err(|error| Error::ValueEncoding(error.to_string()))
+    }
+
+    pub fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        let invalid = |_| Error::InvalidValue(bytes.to_vec());
+        let mut decompressed = vec![];
+
+        match &self.prepared {
+            Some(prepared) => {
+                zstd::stream::read::Decoder::with_prepared_dictionary(bytes, &prepared.decoder)
+                    .and_then(|mut decoder| decoder.read_to_end(&mut decompressed))
+            }
+            None => zstd::stream::read::Decoder::with_buffer(bytes)
+                .and_then(|mut decoder| decoder.read_to_end(&mut decompressed)),
+        }
+        .map_err(invalid)?;
+
+        Ok(decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(i: usize) -> Vec<u8> {
+        format!(
+            r#"{{"id":{},"screen_name":"user_{}","verified":{},"followers_count":{}}}"#,
+            i,
+            i * 7919 % 10007,
+            i.is_multiple_of(3),
+            i * 31
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn round_trip() {
+        let samples = (0..1000).map(sample).collect::<Vec<_>>();
+        let dictionary = ZstdCodec::train_dictionary(&samples, 4096).unwrap();
+
+        let plain = ZstdCodec::new(3);
+        let trained = ZstdCodec::with_dictionary(3, dictionary);
+
+        let value = sample(12345);
+        let plain_compressed = plain.compress(&value).unwrap();
+        let trained_compressed = trained.compress(&value).unwrap();
+
+        assert!(trained_compressed.len() < plain_compressed.len());
+        assert_eq!(plain.decompress(&plain_compressed).unwrap(), value);
+        assert_eq!(trained.decompress(&trained_compressed).unwrap(), value);
+        assert!(plain.decompress(&trained_compressed).is_err());
+        assert!(plain.decompress(b"not compressed").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Per-call configuration for reads and writes.
+
+use rocksdb::{ReadOptions, WriteOptions};
+
+/// Configuration for a single read operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReadConfig {
+    /// Whether blocks read by this operation should be added to the block cache.
+    pub fill_cache: bool,
+    /// Readahead size in bytes for iterators (`None` uses RocksDB's automatic readahead).
+    pub readahead_size: Option<usize>,
+    /// Whether to verify block checksums on read.
+    pub verify_checksums: bool,
+}
+
+impl ReadConfig {
+    /// Configuration for large sequential scans that shouldn't evict other data from the cache.
+    pub fn scan() -> Self {
+        Self {
+            fill_cache: false,
+            readahead_size: Some(2 * 1024 * 1024),
+            verify_checksums: true,
+        }
+    }
+
+    pub fn read_options(&self) -> ReadOptions {
+        let mut options = ReadOptions::default();
+        options.fill_cache(self.fill_cache);
+        options.set_verify_checksums(self.verify_checksums);
+
+        if let Some(readahead_size) = self.readahead_size {
+            options.set_readahead_size(readahead_size);
+        }
+
+        options
+    }
+}
+
+impl Default for ReadConfig {
+    fn default() -> Self {
+        Self {
+            fill_cache: true,
+            readahead_size: None,
+            verify_checksums: true,
+        }
+    }
+}
+
+/// Configuration for a single write operation.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WriteConfig {
+    /// Whether the write should be synced to disk before returning.
+    pub sync: bool,
+    /// Whether to skip the write-ahead log (writes may be lost on a crash).
+    pub disable_wal: bool,
+    /// Whether the write should be throttled in favor of higher-priority writes.
+    pub low_pri: bool,
+    /// Whether the write should fail instead of waiting if it would be delayed by a write stall.
+    pub no_slowdown: bool,
+}
+
+impl WriteConfig {
+    /// Configuration for latency-insensitive writes that must survive a crash.
+    pub fn durable() -> Self {
+        Self {
+            sync: true,
+            ..Self::default()
+        }
+    }
+
+    /// Configuration for bulk loads that can be re-run from scratch if interrupted.
+    pub fn bulk() -> Self {
+        Self {
+            disable_wal: true,
+            low_pri: true,
+            ..Self::default()
+        }
+    }
+
+    /// Configuration for background writes (such as backfills) that should yield to other writes.
+    pub fn background() -> Self {
+        Self {
+            low_pri: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn write_options(&self) -> WriteOptions {
+        let mut options = WriteOptions::default();
+        options.set_sync(self.sync);
+        options.disable_wal(self.disable_wal);
+        options.set_low_pri(self.low_pri);
+        options.set_no_slowdown(self.no_slowdown);
+        options
+    }
+}
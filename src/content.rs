@@ -0,0 +1,64 @@
+//! Content-addressed storage.
+
+use crate::{error, mode, Table};
+use std::marker::PhantomData;
+
+/// A hash function for content addressing (for example SHA-256 or BLAKE3).
+pub trait ContentHasher {
+    type Digest;
+
+    fn hash(bytes: &[u8]) -> Self::Digest;
+}
+
+/// A wrapper for a table whose keys are hashes of its encoded values.
+///
+/// Inserting a value that's already in the table doesn't write anything, so identical values are
+/// stored once.
+pub struct ContentAddressed<M, T, H> {
+    table: T,
+    _mode: PhantomData<M>,
+    _hasher: PhantomData<H>,
+}
+
+impl<M: mode::Mode, T: Table<M>, H: ContentHasher<Digest = T::Key>> ContentAddressed<M, T, H> {
+    pub fn new(table: T) -> Self {
+        Self {
+            table,
+            _mode: PhantomData,
+            _hasher: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    /// Insert a value if it isn't already present, returning its key.
+    pub fn insert(&self, value: &T::Value) -> Result<T::Key, T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let value_bytes = T::value_to_bytes(value)?;
+        let key = H::hash(value_bytes.as_ref());
+        let key_bytes = T::key_to_bytes(&key)?;
+        let db = &self.table.database().db;
+
+        if db
+            .get_pinned(&key_bytes)
+            .map_err(error::Error::from)?
+            .is_none()
+        {
+            db.put(key_bytes, value_bytes).map_err(error::Error::from)?;
+        }
+
+        Ok(key)
+    }
+
+    pub fn lookup(&self, key: &T::Key) -> Result<Option<T::Value>, T::Error> {
+        self.table.lookup_key(key)
+    }
+
+    pub fn contains(&self, key: &T::Key) -> Result<bool, T::Error> {
+        self.table.contains_key(key)
+    }
+}
@@ -0,0 +1,150 @@
+//! Comparison of the contents of two tables (for example a table and its replica).
+
+use crate::{comparator::Comparator, error, mode, Table};
+use rocksdb::DBRawIterator;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+/// A difference between two tables.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DiffItem<K, V> {
+    OnlyLeft { key: K, value: V },
+    OnlyRight { key: K, value: V },
+    Changed { key: K, left: V, right: V },
+}
+
+/// A difference between two tables with the keys and values of the given table.
+pub type TableDiffItem<M, T> = DiffItem<<T as Table<M>>::Key, <T as Table<M>>::Value>;
+
+/// Iterate over the differences between two tables in key order, by merging their iterators.
+///
+/// The tables must use the same key and value encodings and the same key order (the left table's
+/// comparator is used), and values are compared by their encodings.
+pub fn diff<'a, ML, MR, L, R>(left: &'a L, right: &'a R) -> DiffIterator<'a, ML, MR, L, R>
+where
+    ML: mode::Mode,
+    MR: mode::Mode,
+    L: Table<ML>,
+    R: Table<MR, Key = L::Key, Value = L::Value>,
+    L::Error: From<R::Error>,
+{
+    let mut left_underlying = left.database().db.raw_iterator();
+    left_underlying.seek_to_first();
+
+    let mut right_underlying = right.database().db.raw_iterator();
+    right_underlying.seek_to_first();
+
+    DiffIterator {
+        left: left_underlying,
+        right: right_underlying,
+        comparator: L::comparator(),
+        done: false,
+        _mode: PhantomData,
+        _table: PhantomData,
+    }
+}
+
+pub struct DiffIterator<'a, ML, MR, L, R> {
+    left: DBRawIterator<'a>,
+    right: DBRawIterator<'a>,
+    comparator: Option<Comparator>,
+    done: bool,
+    _mode: PhantomData<(ML, MR)>,
+    _table: PhantomData<(L, R)>,
+}
+
+impl<ML, MR, L, R> DiffIterator<'_, ML, MR, L, R>
+where
+    L: Table<ML>,
+    R: Table<MR, Key = L::Key, Value = L::Value>,
+    L::Error: From<R::Error>,
+{
+    fn compare(&self, left_key_bytes: &[u8], right_key_bytes: &[u8]) -> Ordering {
+        match &self.comparator {
+            Some(comparator) => comparator.compare(left_key_bytes, right_key_bytes),
+            None => left_key_bytes.cmp(right_key_bytes),
+        }
+    }
+
+    fn next_item(&mut self) -> Result<Option<TableDiffItem<ML, L>>, L::Error> {
+        loop {
+            let ordering = match (self.left.key(), self.right.key()) {
+                (Some(left_key_bytes), Some(right_key_bytes)) => {
+                    self.compare(left_key_bytes, right_key_bytes)
+                }
+                (Some(_), None) => {
+                    self.right.status().map_err(error::Error::from)?;
+                    Ordering::Less
+                }
+                (None, Some(_)) => {
+                    self.left.status().map_err(error::Error::from)?;
+                    Ordering::Greater
+                }
+                (None, None) => {
+                    self.left.status().map_err(error::Error::from)?;
+                    self.right.status().map_err(error::Error::from)?;
+
+                    return Ok(None);
+                }
+            };
+
+            let item = match ordering {
+                Ordering::Less => DiffItem::OnlyLeft {
+                    key: L::bytes_to_key(Cow::from(self.left.key().unwrap_or_default()))?,
+                    value: L::bytes_to_value(Cow::from(self.left.value().unwrap_or_default()))?,
+                },
+                Ordering::Greater => DiffItem::OnlyRight {
+                    key: R::bytes_to_key(Cow::from(self.right.key().unwrap_or_default()))?,
+                    value: R::bytes_to_value(Cow::from(self.right.value().unwrap_or_default()))?,
+                },
+                Ordering::Equal => {
+                    let left_value_bytes = self.left.value().unwrap_or_default();
+                    let right_value_bytes = self.right.value().unwrap_or_default();
+
+                    if left_value_bytes == right_value_bytes {
+                        self.left.next();
+                        self.right.next();
+                        continue;
+                    }
+
+                    DiffItem::Changed {
+                        key: L::bytes_to_key(Cow::from(self.left.key().unwrap_or_default()))?,
+                        left: L::bytes_to_value(Cow::from(left_value_bytes))?,
+                        right: R::bytes_to_value(Cow::from(right_value_bytes))?,
+                    }
+                }
+            };
+
+            if ordering != Ordering::Greater {
+                self.left.next();
+            }
+
+            if ordering != Ordering::Less {
+                self.right.next();
+            }
+
+            return Ok(Some(item));
+        }
+    }
+}
+
+impl<ML, MR, L, R> Iterator for DiffIterator<'_, ML, MR, L, R>
+where
+    L: Table<ML>,
+    R: Table<MR, Key = L::Key, Value = L::Value>,
+    L::Error: From<R::Error>,
+{
+    type Item = Result<TableDiffItem<ML, L>, L::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            let result = self.next_item().transpose();
+            self.done = !matches!(result, Some(Ok(_)));
+
+            result
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Transparent encryption of values.
+
+use crate::{error, mode, Entry, Table};
+use rocksdb::{DBIterator, IteratorMode};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// An authenticated encryption scheme (such as AES-GCM or ChaCha20-Poly1305).
+///
+/// Implementations are responsible for generating nonces and including them in the ciphertext.
+pub trait Cipher {
+    fn encrypt(&self, plaintext: &[u8], associated_data: &[u8]) -> Vec<u8>;
+
+    /// Decrypt a ciphertext, returning `None` if it fails authentication.
+    fn decrypt(&self, ciphertext: &[u8], associated_data: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// A wrapper for a table that encrypts encoded values.
+///
+/// Keys are stored in plaintext (so ordering and index lookups still work), and each encoded key
+/// is used as associated data for its value, so that a ciphertext can't be moved to another key.
+/// The underlying table's own lookup and iteration methods will return undecodable values.
+pub struct EncryptedTable<M, T, C> {
+    table: T,
+    cipher: C,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::Mode, T: Table<M>, C: Cipher> EncryptedTable<M, T, C> {
+    pub fn new(table: T, cipher: C) -> Self {
+        Self {
+            table,
+            cipher,
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub fn put(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let key_bytes = T::key_to_bytes(key)?;
+        let value_bytes = T::value_to_bytes(value)?;
+        let ciphertext = self
+            .cipher
+            .encrypt(value_bytes.as_ref(), key_bytes.as_ref());
+
+        Ok(self
+            .table
+            .database()
+            .db
+            .put(key_bytes, ciphertext)
+            .map_err(error::Error::from)?)
+    }
+
+    pub fn lookup_key(&self, key: &T::Key) -> Result<Option<T::Value>, T::Error> {
+        let key_bytes = T::key_to_bytes(key)?;
+
+        match self
+            .table
+            .database()
+            .db
+            .get_pinned(&key_bytes)
+            .map_err(error::Error::from)?
+        {
+            Some(ciphertext) => Ok(Some(decrypt_value::<M, T, C>(
+                &self.cipher,
+                key_bytes.as_ref(),
+                &ciphertext,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn iter(&self) -> EncryptedIterator<'_, M, T, C> {
+        EncryptedIterator {
+            underlying: self.table.database().db.iterator(IteratorMode::Start),
+            cipher: &self.cipher,
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }
+}
+
+fn decrypt_value<M, T: Table<M>, C: Cipher>(
+    cipher: &C,
+    key_bytes: &[u8],
+    ciphertext: &[u8],
+) -> Result<T::Value, T::Error> {
+    let plaintext = cipher
+        .decrypt(ciphertext, key_bytes)
+        .ok_or_else(|| error::Error::InvalidValue(ciphertext.to_vec()))?;
+
+    T::bytes_to_value(Cow::from(plaintext))
+}
+
+/// Iterates over decrypted entries in an encrypted table.
+pub struct EncryptedIterator<'a, M, T, C> {
+    underlying: DBIterator<'a>,
+    cipher: &'a C,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: Table<M>, C: Cipher> Iterator for EncryptedIterator<'a, M, T, C> {
+    type Item = Result<Entry<M, T>, T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.underlying.next().map(|result| {
+            let (key_bytes, ciphertext) = result.map_err(error::Error::from)?;
+            let value = decrypt_value::<M, T, C>(self.cipher, &key_bytes, &ciphertext)?;
+
+            Ok((T::bytes_to_key(Cow::from(Vec::from(key_bytes)))?, value))
+        })
+    }
+}
@@ -6,4 +6,31 @@ pub enum Error {
     InvalidKey(Vec<u8>),
     #[error("Invalid value bytes")]
     InvalidValue(Vec<u8>),
+    #[error("Invalid key bytes at offset {offset}")]
+    InvalidKeyAt { bytes: Vec<u8>, offset: usize },
+    #[error("Invalid value bytes at offset {offset}")]
+    InvalidValueAt { bytes: Vec<u8>, offset: usize },
+    #[error("Value encoding error")]
+    ValueEncoding(String),
+    #[error("Invalid cursor")]
+    InvalidCursor(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("Stored {option} {stored:?} doesn't match {expected:?}")]
+    OptionsMismatch {
+        option: &'static str,
+        stored: Option<String>,
+        expected: Option<String>,
+    },
+    #[error("Stored schema {stored:?} doesn't match {expected:?}")]
+    SchemaMismatch {
+        stored: Box<crate::schema::Fingerprint>,
+        expected: Box<crate::schema::Fingerprint>,
+    },
+    #[error("Write-ahead log can't be fully read at sequence number {0}")]
+    IncompleteLog(u64),
+    #[error("Out of order key bytes")]
+    OutOfOrderKey(Vec<u8>),
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
 }
@@ -0,0 +1,118 @@
+//! Tracing instrumentation for table operations (enabled by the `tracing` feature).
+
+use crate::Table;
+
+/// Run an operation in a span identifying the table, and warn if it exceeds the table's slow
+/// operation threshold.
+#[cfg(feature = "tracing")]
+pub(crate) fn traced<M, T: Table<M>, R, F: FnOnce() -> R>(
+    operation: &'static str,
+    key_len: usize,
+    f: F,
+) -> R {
+    let span = tracing::debug_span!("rocksdb_table", table = T::name(), operation, key_len);
+    let _entered = span.enter();
+    let start = std::time::Instant::now();
+
+    let result = f();
+
+    if let Some(threshold) = T::slow_operation_threshold() {
+        let elapsed = start.elapsed();
+
+        if elapsed >= threshold {
+            tracing::warn!(
+                elapsed_micros = elapsed.as_micros() as u64,
+                "Slow RocksDB table operation"
+            );
+        }
+    }
+
+    result
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline]
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn traced<M, T: Table<M>, R, F: FnOnce() -> R>(
+    _operation: &'static str,
+    _key_len: usize,
+    f: F,
+) -> R {
+    f()
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use crate::{mode, raw::RawTable};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the table and operation fields of each new span.
+    #[derive(Default)]
+    struct SpanRecorder {
+        next_id: AtomicU64,
+        spans: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut Vec<String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push(format!("{}={}", field.name(), value));
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = vec![];
+            span.record(&mut FieldVisitor(&mut fields));
+            self.spans.lock().unwrap().push(fields.join(" "));
+
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+        fn event(&self, _event: &Event<'_>) {}
+        fn enter(&self, _span: &Id) {}
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn spans() {
+        let recorder = SpanRecorder::default();
+        let spans = recorder.spans.clone();
+
+        let directory = tempfile::tempdir().unwrap();
+        let table = RawTable::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        tracing::subscriber::with_default(recorder, || {
+            table.put(&b"abc".to_vec(), &b"def".to_vec()).unwrap();
+            table.lookup_key(&b"abc".to_vec()).unwrap();
+            table.lookup_index(&b"a".to_vec()).count();
+            table.iter_page(None, 10).unwrap();
+        });
+
+        let table_name = format!("table={}", RawTable::<mode::Writeable>::name());
+
+        assert_eq!(
+            *spans.lock().unwrap(),
+            vec![
+                format!("{table_name} operation=put"),
+                format!("{table_name} operation=lookup"),
+                format!("{table_name} operation=lookup_index"),
+                format!("{table_name} operation=iter_page"),
+            ]
+        );
+    }
+}
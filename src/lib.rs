@@ -1,12 +1,66 @@
 //! Some helpers for working with RocksDB databases.
 
-use rocksdb::{DBCompressionType, DBIterator, IteratorMode, Options, DB};
+use rocksdb::{
+    ColumnFamily, ColumnFamilyDescriptor, DBCompressionType, DBIterator, DBPinnableSlice,
+    DBRawIterator, Direction, IngestExternalFileOptions, IteratorMode, Options, ReadOptions,
+    WriteBatch, DB, DEFAULT_COLUMN_FAMILY_NAME,
+};
 use std::borrow::Cow;
 use std::marker::PhantomData;
-use std::path::Path;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+pub mod audit;
+pub mod block;
+pub mod borrowed;
+pub mod builder;
+#[cfg(feature = "lru")]
+pub mod cached;
+pub mod changes;
+pub mod chunked;
+pub mod codec;
+pub mod compaction;
+pub mod comparator;
+#[cfg(feature = "zstd")]
+pub mod compression;
+pub mod config;
+pub mod content;
+pub mod diff;
+pub mod encrypted;
 pub mod error;
+mod instrument;
+pub mod memory;
+pub mod merge;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod migration;
+pub mod page;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod perf;
+pub mod prefix;
+pub mod presets;
+pub mod raw;
+pub mod replication;
+pub mod retention;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod soft_delete;
+pub mod sst;
+pub mod stall;
+pub mod statistics;
+pub mod stored;
+pub mod subscription;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod verify;
+pub mod versioned;
+pub mod wal;
 
 /// Marker structs that indicate access mode.
 pub mod mode {
@@ -80,9 +134,107 @@ pub mod mode {
 pub struct Database<M> {
     pub db: Arc<DB>,
     options: Options,
+    // Declared after the database so that it's dropped (and deleted) after the database is closed.
+    _temporary_directory: Option<Arc<tempfile::TempDir>>,
     _mode: PhantomData<M>,
 }
 
+impl<M> Database<M> {
+    /// The handle for the default column family (which we always open explicitly).
+    pub fn default_cf(&self) -> &ColumnFamily {
+        self.db
+            .cf_handle(DEFAULT_COLUMN_FAMILY_NAME)
+            .expect("Default column family is always opened")
+    }
+}
+
+/// The default column family uses the database options (opening it by name alone would silently
+/// replace any column family options like compression or merge operators with the defaults).
+fn default_cf_descriptor(options: &Options) -> ColumnFamilyDescriptor {
+    ColumnFamilyDescriptor::new(DEFAULT_COLUMN_FAMILY_NAME, options.clone())
+}
+
+/// The number of deletions written at once by [`Table::apply_retention`].
+const RETENTION_BATCH_SIZE: usize = 1024;
+
+/// The number of entries written at once by [`Table::put_all`].
+const PUT_ALL_BATCH_SIZE: usize = 1024;
+
+/// The property used by [`Table::truncate`] to measure the space reclaimed.
+const TOTAL_SST_FILES_SIZE_PROPERTY: &str = "rocksdb.total-sst-files-size";
+
+/// Descriptors for the reserved column families (schema metadata and audit records) that exist in
+/// the given database.
+fn reserved_cf_descriptors<P: AsRef<Path>>(path: P) -> Vec<ColumnFamilyDescriptor> {
+    DB::list_cf(&Options::default(), path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| {
+            name == schema::META_COLUMN_FAMILY_NAME || name == audit::AUDIT_COLUMN_FAMILY_NAME
+        })
+        .map(|name| ColumnFamilyDescriptor::new(name, Options::default()))
+        .collect()
+}
+
+/// Open a database for a table, with a time-to-live if one is given, and check the table's schema
+/// fingerprint.
+fn open_default_cf<M: mode::Mode, T: Table<M>, P: AsRef<Path>>(
+    options: &Options,
+    path: P,
+    ttl: Option<Duration>,
+) -> Result<DB, error::Error> {
+    let mut db = open_cfs::<M, _>(options, path, ttl)?;
+    schema::check::<M, T>(&mut db)?;
+    audit::prepare::<M, T>(&mut db)?;
+
+    Ok(db)
+}
+
+/// Open a database with the default column family (and, in read-only mode, the schema metadata
+/// and audit column families, if they exist), with a time-to-live if one is given.
+fn open_cfs<M: mode::Mode, P: AsRef<Path>>(
+    options: &Options,
+    path: P,
+    ttl: Option<Duration>,
+) -> Result<DB, rocksdb::Error> {
+    if M::is_read_only() {
+        let descriptors =
+            std::iter::once(default_cf_descriptor(options)).chain(reserved_cf_descriptors(&path));
+
+        return DB::open_cf_descriptors_read_only(options, path, descriptors, true);
+    }
+
+    // Writeable databases must be opened with all of their column families, so any other column
+    // families (for example ones created by other applications) are opened with default options.
+    let other_names = DB::list_cf(&Options::default(), &path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| name != DEFAULT_COLUMN_FAMILY_NAME);
+
+    let descriptors = std::iter::once(default_cf_descriptor(options))
+        .chain(other_names.map(|name| ColumnFamilyDescriptor::new(name, Options::default())));
+
+    if let Some(ttl) = ttl {
+        DB::open_cf_descriptors_with_ttl(options, path, descriptors, ttl)
+    } else {
+        DB::open_cf_descriptors(options, path, descriptors)
+    }
+}
+
+/// The result of a key existence check that may not be able to give a definitive answer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyMayExist<V> {
+    /// The key is definitely not in the table.
+    Absent,
+    /// The key may be in the table.
+    Maybe,
+    /// The key is in the table, and its value was available without any I/O.
+    Present(V),
+}
+
+/// A decoded key-value pair from a table.
+pub type Entry<M, T> = (<T as Table<M>>::Key, <T as Table<M>>::Value);
+
 /// A database table.
 pub trait Table<M>: Sized {
     type Counts;
@@ -102,6 +254,15 @@ pub trait Table<M>: Sized {
     fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error>;
     fn index_to_bytes(index: &Self::Index) -> Result<Self::IndexBytes, Self::Error>;
 
+    /// Append the encoded key to a buffer.
+    ///
+    /// This is used for writes and when encoding many keys at once (with a buffer that's reused
+    /// across keys), and tables can override it to avoid allocating for each key.
+    fn encode_key_into(key: &Self::Key, buffer: &mut Vec<u8>) -> Result<(), Self::Error> {
+        buffer.extend_from_slice(Self::key_to_bytes(key)?.as_ref());
+        Ok(())
+    }
+
     fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error>;
     fn bytes_to_value(bytes: Cow<[u8]>) -> Result<Self::Value, Self::Error>;
 
@@ -109,10 +270,141 @@ pub trait Table<M>: Sized {
         None
     }
 
+    /// The merge operator to install when the table is opened with its defaults.
+    fn merge_operator() -> Option<merge::MergeOperator> {
+        None
+    }
+
+    /// The compaction filter to install when the table is opened with its defaults.
+    fn compaction_filter() -> Option<compaction::CompactionFilter> {
+        None
+    }
+
+    /// The key comparator to install when the table is opened with its defaults.
+    ///
+    /// This is only needed when the table's key order can't be expressed by an order-preserving
+    /// encoding (see [`codec`]). Index lookups, range iteration, and size estimates use bounds
+    /// computed in byte order, so they may not behave as expected with a custom comparator.
+    fn comparator() -> Option<comparator::Comparator> {
+        None
+    }
+
+    /// The prefix extractor to install when the table is opened with its defaults.
+    ///
+    /// This is usually the length of the table's index, but tables with keys shorter than the
+    /// index length should use a capped extractor (or none at all).
+    fn prefix_extractor() -> Option<prefix::PrefixExtractor> {
+        None
+    }
+
+    /// The block-based table configuration (filters, block size, and index type).
+    fn block_config() -> Option<block::BlockConfig> {
+        None
+    }
+
+    /// The length of every encoded key, if keys have a fixed width (this is part of the table's
+    /// schema fingerprint).
+    fn key_width() -> Option<usize> {
+        None
+    }
+
+    /// The length of every encoded value, if values have a fixed width.
+    fn value_width() -> Option<usize> {
+        None
+    }
+
+    /// The policy for removing tombstones written by a [`soft_delete::SoftDeleteTable`].
+    fn soft_delete() -> Option<soft_delete::SoftDelete> {
+        None
+    }
+
+    /// Whether the table can be wrapped in an [`audit::AuditedTable`] (this creates the audit
+    /// column family when the table is opened in write mode).
+    fn audited() -> bool {
+        false
+    }
+
+    /// Whether the table's schema fingerprint is stored and checked when the table is opened (this
+    /// creates the schema metadata column family when the table is opened in write mode, and is
+    /// required for migrations).
+    fn schema_fingerprint() -> bool {
+        false
+    }
+
+    /// The time-to-live for entries when the table is opened in write mode.
+    ///
+    /// Expired entries are removed during compaction, so they may still be returned by reads for
+    /// some time after they expire. Note that RocksDB stores a timestamp with each value in a
+    /// table with a time-to-live, and the time-to-live isn't applied in read-only or secondary
+    /// mode, so these tables shouldn't be opened in those modes (values would still have the
+    /// timestamp appended).
+    fn default_ttl() -> Option<Duration> {
+        None
+    }
+
+    /// The name used to identify this table in instrumentation.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// The duration after which an operation is logged as slow (requires the `tracing` feature).
+    fn slow_operation_threshold() -> Option<Duration> {
+        None
+    }
+
+    /// Apply this table's default configuration to the given options.
+    fn configure_options(mut options: Options) -> Options {
+        if let Some(compression_type) = Self::default_compression_type() {
+            options.set_compression_type(compression_type);
+        }
+
+        if let Some(merge_operator) = Self::merge_operator() {
+            merge_operator.configure(&mut options);
+        }
+
+        let compaction_filter = match Self::soft_delete() {
+            Some(soft_delete) => Some(soft_delete.compaction_filter(Self::compaction_filter())),
+            None => Self::compaction_filter(),
+        };
+
+        if let Some(compaction_filter) = compaction_filter {
+            compaction_filter.configure(&mut options);
+        }
+
+        if let Some(comparator) = Self::comparator() {
+            comparator.configure(&mut options);
+        }
+
+        if let Some(prefix_extractor) = Self::prefix_extractor() {
+            prefix_extractor.configure(&mut options);
+        }
+
+        if let Some(block_config) = Self::block_config() {
+            block_config.configure(&mut options);
+        }
+
+        options
+    }
+
     fn statistics(&self) -> Option<String> {
         self.database().options.get_statistics()
     }
 
+    /// Run a function on this table with perf counters enabled (see [`perf::with_perf_context`]).
+    fn with_perf_context<R, F: FnOnce(&Self) -> R>(
+        &self,
+        level: rocksdb::perf::PerfStatsLevel,
+        f: F,
+    ) -> (R, perf::PerfReport) {
+        perf::with_perf_context(level, || f(self))
+    }
+
+    /// Statistics parsed from [`Table::statistics`] (if they're enabled).
+    fn parsed_statistics(&self) -> Option<statistics::Statistics> {
+        self.statistics()
+            .map(|statistics| statistics::Statistics::parse(&statistics))
+    }
+
     fn get_estimated_key_count(&self) -> Result<Option<u64>, error::Error> {
         Ok(self
             .database()
@@ -120,17 +412,30 @@ pub trait Table<M>: Sized {
             .property_int_value("rocksdb.estimate-num-keys")?)
     }
 
+    /// Count the entries in the table exactly, using a key-only scan that decodes nothing.
+    ///
+    /// See [`Table::get_estimated_key_count`] for a cheaper estimate.
+    fn exact_len(&self) -> Result<u64, error::Error> {
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek_to_first();
+
+        let mut count = 0;
+
+        while underlying.valid() {
+            count += 1;
+            underlying.next();
+        }
+
+        underlying.status()?;
+
+        Ok(count)
+    }
+
     fn open_with_defaults<P: AsRef<Path>>(path: P) -> Result<Self, error::Error>
     where
         M: mode::SinglePath,
     {
-        Self::open(path, |mut options| {
-            if let Some(compression_type) = Self::default_compression_type() {
-                options.set_compression_type(compression_type);
-            }
-
-            options
-        })
+        Self::open(path, Self::configure_options)
     }
 
     fn open_as_secondary_with_defaults<P: AsRef<Path>, S: AsRef<Path>>(
@@ -140,11 +445,86 @@ pub trait Table<M>: Sized {
     where
         M: mode::IsSecondary,
     {
-        Self::open_as_secondary(path, secondary_path, |mut options| {
-            if let Some(compression_type) = Self::default_compression_type() {
-                options.set_compression_type(compression_type);
+        Self::open_as_secondary(path, secondary_path, Self::configure_options)
+    }
+
+    /// Check that an existing database was created with this table's merge operator and prefix
+    /// extractor (a database without an OPTIONS file, such as a new one, always passes).
+    fn check_stored_options<P: AsRef<Path>>(path: P) -> Result<(), error::Error> {
+        if let Some(stored) = stored::StoredOptions::load(path)? {
+            let expected_merge_operator =
+                Self::merge_operator().map(|merge_operator| merge_operator.name().to_string());
+            let expected_prefix_extractor =
+                Self::prefix_extractor().map(|prefix_extractor| prefix_extractor.name());
+
+            if stored.merge_operator != expected_merge_operator {
+                return Err(error::Error::OptionsMismatch {
+                    option: "merge operator",
+                    stored: stored.merge_operator,
+                    expected: expected_merge_operator,
+                });
+            }
+
+            if stored.prefix_extractor != expected_prefix_extractor {
+                return Err(error::Error::OptionsMismatch {
+                    option: "prefix extractor",
+                    stored: stored.prefix_extractor,
+                    expected: expected_prefix_extractor,
+                });
             }
+        }
+
+        Ok(())
+    }
+
+    /// The names of the column families in an existing database.
+    ///
+    /// Tables only use the default column family, but other column families are opened (with
+    /// default options) when a table is opened in write mode, and can be accessed through the
+    /// database's `cf_handle`.
+    fn list_column_families<P: AsRef<Path>>(path: P) -> Result<Vec<String>, error::Error> {
+        Ok(DB::list_cf(&Options::default(), path)?)
+    }
+
+    /// Delete a closed database (using this table's default options).
+    ///
+    /// A separate write-ahead log directory (for example one set with
+    /// [`builder::TableBuilder::wal_dir`]) isn't part of these options, so it isn't removed.
+    fn destroy<P: AsRef<Path>>(path: P) -> Result<(), error::Error> {
+        Ok(DB::destroy(
+            &Self::configure_options(Options::default()),
+            path,
+        )?)
+    }
+
+    /// Try to recover as much data as possible from a closed, corrupted database (this requires
+    /// the table's comparator, if it has one).
+    fn repair<P: AsRef<Path>>(path: P) -> Result<(), error::Error> {
+        Ok(DB::repair(
+            &Self::configure_options(Options::default()),
+            path,
+        )?)
+    }
 
+    /// A builder for opening the table, starting from its default configuration.
+    fn builder() -> builder::TableBuilder<M, Self>
+    where
+        M: mode::SinglePath,
+    {
+        builder::TableBuilder::new()
+    }
+
+    /// Open the table with its default configuration, adjusted by the given function.
+    fn open_with<P: AsRef<Path>, F: FnMut(&mut Options)>(
+        path: P,
+        mut configure: F,
+    ) -> Result<Self, error::Error>
+    where
+        M: mode::SinglePath,
+    {
+        Self::open(path, |options| {
+            let mut options = Self::configure_options(options);
+            configure(&mut options);
             options
         })
     }
@@ -160,16 +540,111 @@ pub trait Table<M>: Sized {
         options.create_if_missing(true);
 
         let options = options_init(options);
+        let db = open_default_cf::<M, Self, _>(&options, path, Self::default_ttl())?;
 
-        let db = if M::is_read_only() {
-            DB::open_for_read_only(&options, path, true)?
-        } else {
-            DB::open(&options, path)?
-        };
+        Ok(Self::from_database(Database {
+            db: Arc::new(db),
+            options,
+            _temporary_directory: None,
+            _mode: PhantomData,
+        }))
+    }
+
+    /// Open the table for an initial bulk load (see [`presets::bulk_load`]).
+    ///
+    /// Automatic compactions are disabled until [`Table::finish_bulk_load`] is called, and writes
+    /// should generally use [`config::WriteConfig::bulk`] to skip the write-ahead log.
+    fn open_for_bulk_load<P: AsRef<Path>, F: FnMut(Options) -> Options>(
+        path: P,
+        mut options_init: F,
+    ) -> Result<Self, error::Error>
+    where
+        M: mode::IsWriteable + mode::SinglePath,
+    {
+        let mut bulk_load = presets::bulk_load();
+
+        Self::open(path, |options| bulk_load(options_init(options)))
+    }
+
+    /// Finish a bulk load by flushing any unlogged writes, re-enabling automatic compactions, and
+    /// compacting the whole table.
+    ///
+    /// Other settings from the bulk load preset (such as the relaxed write stall triggers) remain
+    /// in effect until the table is reopened.
+    fn finish_bulk_load(&self) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let database = self.database();
+
+        database.db.flush().map_err(error::Error::from)?;
+        self.set_options(&[("disable_auto_compactions", "false")])?;
+        database
+            .db
+            .compact_range_cf(database.default_cf(), None::<&[u8]>, None::<&[u8]>);
+
+        Ok(())
+    }
+
+    /// Change mutable options (such as `write_buffer_size` or `disable_auto_compactions`) on the
+    /// open database.
+    ///
+    /// Changes aren't persisted by the table's own configuration, so they're lost when the table is
+    /// reopened.
+    fn set_options(&self, options: &[(&str, &str)]) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let database = self.database();
+
+        Ok(database
+            .db
+            .set_options_cf(database.default_cf(), options)
+            .map_err(error::Error::from)?)
+    }
+
+    /// Open the table in a new temporary directory, which is deleted when the database is dropped.
+    fn open_temporary<F: FnMut(Options) -> Options>(
+        mut options_init: F,
+    ) -> Result<Self, error::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let directory = tempfile::tempdir()?;
+
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        let options = options_init(options);
+        let db = open_default_cf::<M, Self, _>(&options, directory.path(), Self::default_ttl())?;
+
+        Ok(Self::from_database(Database {
+            db: Arc::new(db),
+            options,
+            _temporary_directory: Some(Arc::new(directory)),
+            _mode: PhantomData,
+        }))
+    }
+
+    /// Open the table with the given time-to-live (instead of the table's default).
+    fn open_with_ttl<P: AsRef<Path>, F: FnMut(Options) -> Options>(
+        path: P,
+        ttl: Duration,
+        mut options_init: F,
+    ) -> Result<Self, error::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        let options = options_init(options);
+        let db = open_default_cf::<M, Self, _>(&options, path, Some(ttl))?;
 
         Ok(Self::from_database(Database {
             db: Arc::new(db),
             options,
+            _temporary_directory: None,
             _mode: PhantomData,
         }))
     }
@@ -186,11 +661,21 @@ pub trait Table<M>: Sized {
         options.create_if_missing(true);
 
         let options = options_init(options);
-        let db = DB::open_as_secondary(&options, path.as_ref(), secondary_path.as_ref())?;
+        let descriptors = std::iter::once(default_cf_descriptor(&options))
+            .chain(reserved_cf_descriptors(path.as_ref()));
+
+        let mut db = DB::open_cf_descriptors_as_secondary(
+            &options,
+            path.as_ref(),
+            secondary_path.as_ref(),
+            descriptors,
+        )?;
+        schema::check::<M, Self>(&mut db)?;
 
         Ok(Self::from_database(Database {
             db: Arc::new(db),
             options,
+            _temporary_directory: None,
             _mode: PhantomData,
         }))
     }
@@ -199,389 +684,3787 @@ pub trait Table<M>: Sized {
     where
         M: 'static,
     {
-        TableIterator {
-            underlying: self.database().db.iterator(IteratorMode::Start),
-            _mode: PhantomData,
-            _table: PhantomData,
-        }
+        self.iter_opt(&config::ReadConfig::default())
     }
 
-    fn iter_selected_values<P: Fn(&Self::Key) -> bool>(
-        &self,
-        pred: P,
-    ) -> SelectedValueTableIterator<'_, M, Self, P>
+    /// Iterate over encoded keys and values without decoding them.
+    ///
+    /// This can be used to inspect a table whose entries can't be decoded.
+    fn iter_raw(&self) -> DBIterator<'_>
+    where
+        M: 'static,
+    {
+        self.database().db.iterator(IteratorMode::Start)
+    }
+
+    fn iter_opt(&self, config: &config::ReadConfig) -> TableIterator<'_, M, Self>
     where
         M: 'static,
     {
-        SelectedValueTableIterator {
-            underlying: self.database().db.iterator(IteratorMode::Start),
-            pred,
+        instrument::traced::<M, Self, _, _>("iter", 0, || TableIterator {
+            underlying: self
+                .database()
+                .db
+                .iterator_opt(IteratorMode::Start, config.read_options()),
             _mode: PhantomData,
             _table: PhantomData,
-        }
+        })
     }
 
-    fn lookup_key(&self, key: &Self::Key) -> Result<Option<Self::Value>, Self::Error> {
-        let key_bytes = Self::key_to_bytes(key)?;
-        self.database()
-            .db
-            .get_pinned(key_bytes)
-            .map_err(error::Error::from)?
-            .map_or(Ok(None), |value_bytes| {
-                Self::bytes_to_value(Cow::from(value_bytes.as_ref())).map(Some)
-            })
+    /// Iterate over the table in reverse key order.
+    fn iter_rev(&self) -> TableIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        instrument::traced::<M, Self, _, _>("iter_rev", 0, || TableIterator {
+            underlying: self.database().db.iterator(IteratorMode::End),
+            _mode: PhantomData,
+            _table: PhantomData,
+        })
     }
 
-    fn lookup_index(&self, index: &Self::Index) -> IndexIterator<'_, M, Self>
+    /// Iterate in the given direction, starting from the given key (or the first key after it in
+    /// that direction, if it is not present).
+    fn iter_from(
+        &self,
+        key: &Self::Key,
+        direction: Direction,
+    ) -> Result<TableIterator<'_, M, Self>, Self::Error>
     where
         M: 'static,
     {
-        match Self::index_to_bytes(index) {
-            Ok(index_bytes) => IndexIterator::ValidIndex {
-                underlying: self.database().db.prefix_iterator(&index_bytes),
-                index_bytes,
+        let key_bytes = Self::key_to_bytes(key)?;
+        let key_len = key_bytes.as_ref().len();
+
+        Ok(instrument::traced::<M, Self, _, _>(
+            "iter_from",
+            key_len,
+            || TableIterator {
+                underlying: self
+                    .database()
+                    .db
+                    .iterator(IteratorMode::From(key_bytes.as_ref(), direction)),
                 _mode: PhantomData,
                 _table: PhantomData,
             },
-            Err(error) => IndexIterator::InvalidIndex { error: Some(error) },
-        }
+        ))
     }
 
-    fn lookup_index_selected_values<P: Fn(&Self::Key) -> bool>(
+    /// Iterate over the entries with keys in the given range.
+    fn iter_range(
         &self,
-        index: &Self::Index,
-        pred: P,
-    ) -> SelectedValueIndexIterator<'_, M, Self, P>
+        start: Bound<&Self::Key>,
+        end: Bound<&Self::Key>,
+    ) -> Result<TableIterator<'_, M, Self>, Self::Error>
     where
         M: 'static,
     {
-        match Self::index_to_bytes(index) {
-            Ok(index_bytes) => SelectedValueIndexIterator::ValidIndex {
-                underlying: self.database().db.prefix_iterator(&index_bytes),
-                index_bytes,
-                pred,
+        let range = KeyRange::new::<M, Self>(start, end)?;
+        let mut options = ReadOptions::default();
+
+        if let Some(lower) = range.lower {
+            options.set_iterate_lower_bound(lower);
+        }
+
+        if let Some(upper) = range.upper {
+            options.set_iterate_upper_bound(upper);
+        }
+
+        Ok(instrument::traced::<M, Self, _, _>("iter_range", 0, || {
+            TableIterator {
+                underlying: self
+                    .database()
+                    .db
+                    .iterator_opt(IteratorMode::Start, options),
                 _mode: PhantomData,
                 _table: PhantomData,
-            },
-            Err(error) => SelectedValueIndexIterator::InvalidIndex { error: Some(error) },
-        }
+            }
+        }))
     }
 
-    fn put(&self, key: &Self::Key, value: &Self::Value) -> Result<(), Self::Error>
-    where
-        M: mode::IsWriteable,
-    {
-        let key_bytes = Self::key_to_bytes(key)?;
-        let value_bytes = Self::value_to_bytes(value)?;
-        Ok(self
+    /// Estimate the on-disk size in bytes of the entries with keys in the given range.
+    ///
+    /// This estimate is computed at the granularity of SST files (so any file that overlaps the
+    /// range is counted in full), and does not include data that has not been flushed yet.
+    fn approximate_size(
+        &self,
+        start: Bound<&Self::Key>,
+        end: Bound<&Self::Key>,
+    ) -> Result<u64, Self::Error> {
+        let range = KeyRange::new::<M, Self>(start, end)?;
+        let live_files = self
             .database()
             .db
-            .put(key_bytes, value_bytes)
-            .map_err(error::Error::from)?)
-    }
+            .live_files()
+            .map_err(error::Error::from)?;
 
-    fn catch_up_with_primary(&self) -> Result<(), Self::Error>
-    where
-        M: mode::IsSecondary,
-    {
-        Ok(self
-            .database()
-            .db
-            .try_catch_up_with_primary()
-            .map_err(error::Error::from)?)
+        Ok(live_files
+            .into_iter()
+            .filter(|live_file| {
+                live_file.column_family_name == DEFAULT_COLUMN_FAMILY_NAME
+                    && range.overlaps(live_file.start_key.as_deref(), live_file.end_key.as_deref())
+            })
+            .map(|live_file| live_file.size as u64)
+            .sum())
     }
-}
 
-pub struct TableIterator<'a, M, T> {
-    underlying: DBIterator<'a>,
-    _mode: PhantomData<M>,
-    _table: PhantomData<T>,
-}
+    /// Read a page of at most `limit` entries, starting after the given cursor (or from the start
+    /// of the table if there is no cursor).
+    ///
+    /// The limit must be positive, since an empty page couldn't indicate where the next page
+    /// starts.
+    fn iter_page(
+        &self,
+        cursor: Option<&page::Cursor>,
+        limit: usize,
+    ) -> Result<page::Page<Self::Key, Self::Value>, Self::Error> {
+        if limit == 0 {
+            return Err(error::Error::InvalidConfiguration(
+                "Page limit must be positive".to_string(),
+            )
+            .into());
+        }
 
-impl<'a, M: mode::Mode, T: Table<M>> Iterator for TableIterator<'a, M, T> {
-    type Item = Result<(T::Key, T::Value), T::Error>;
+        let key_len = cursor.map_or(0, |cursor| cursor.as_bytes().len());
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.underlying.next().map(|result| {
-            result
-                .map_err(|error| T::Error::from(error.into()))
-                .and_then(|(key_bytes, value_bytes)| {
-                    T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
-                        T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
-                            .map(|value| (key, value))
-                    })
-                })
+        instrument::traced::<M, Self, _, _>("iter_page", key_len, || {
+            let mut underlying = self.database().db.raw_iterator();
+
+            match cursor {
+                Some(cursor) => underlying.seek(key_successor(cursor.as_bytes())),
+                None => underlying.seek_to_first(),
+            }
+
+            let mut entries = Vec::with_capacity(limit);
+            let mut last_key_bytes = None;
+
+            while entries.len() < limit {
+                match underlying.item() {
+                    Some((key_bytes, value_bytes)) => {
+                        let key = Self::bytes_to_key(Cow::from(key_bytes))?;
+                        let value = Self::bytes_to_value(Cow::from(value_bytes))?;
+
+                        entries.push((key, value));
+                        last_key_bytes = Some(key_bytes.to_vec());
+                        underlying.next();
+                    }
+                    None => break,
+                }
+            }
+
+            underlying.status().map_err(error::Error::from)?;
+
+            Ok(page::Page {
+                entries,
+                next: last_key_bytes
+                    .filter(|_| underlying.valid())
+                    .map(page::Cursor::from_bytes),
+            })
         })
     }
-}
 
-/// Allows selection of values to decode (if for example this is expensive).
-pub struct SelectedValueTableIterator<'a, M, T, P> {
-    underlying: DBIterator<'a>,
-    pred: P,
-    _mode: PhantomData<M>,
-    _table: PhantomData<T>,
-}
+    /// Iterate over entries with keys after the table's last key when the iterator is created.
+    ///
+    /// Only keys appended beyond that key (and beyond each key returned) are seen, so this is
+    /// intended for tables with increasing keys (for example timestamps); updates to existing keys
+    /// and new keys that sort before the last key aren't returned.
+    ///
+    /// The iterator returns `None` when it has caught up with the table, but may return new entries
+    /// on later calls to `next` (i.e. it is not fused), so it can be polled like a log.
+    fn tail(&self) -> TailIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        instrument::traced::<M, Self, _, _>("tail", 0, || {
+            // Tailing iterators only support forward iteration, so we find the current end of the
+            // table with a regular iterator.
+            let mut last = self.database().db.raw_iterator();
+            last.seek_to_last();
 
-impl<'a, M: mode::Mode, T: Table<M>, P: Fn(&T::Key) -> bool> Iterator
-    for SelectedValueTableIterator<'a, M, T, P>
-{
-    type Item = Result<(T::Key, Option<T::Value>), T::Error>;
+            let mut options = ReadOptions::default();
+            options.set_tailing(true);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.underlying.next().map(|result| {
-            result
-                .map_err(|error| T::Error::from(error.into()))
-                .and_then(|(key_bytes, value_bytes)| {
-                    T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
-                        if (self.pred)(&key) {
-                            T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
-                                .map(|value| (key, Some(value)))
-                        } else {
-                            Ok((key, None))
-                        }
-                    })
+            TailIterator {
+                underlying: self.database().db.raw_iterator_opt(options),
+                last_key_bytes: last.key().map(|key_bytes| key_bytes.to_vec()),
+                _mode: PhantomData,
+                _table: PhantomData,
+            }
+        })
+    }
+
+    /// Iterate over keys only, without copying or decoding values.
+    fn iter_keys(&self) -> KeyIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        instrument::traced::<M, Self, _, _>("iter_keys", 0, || {
+            let mut underlying = self.database().db.raw_iterator();
+            underlying.seek_to_first();
+
+            KeyIterator {
+                underlying,
+                done: false,
+                _mode: PhantomData,
+                _table: PhantomData,
+            }
+        })
+    }
+
+    fn iter_selected_values<P: Fn(&Self::Key) -> bool>(
+        &self,
+        pred: P,
+    ) -> SelectedValueTableIterator<'_, M, Self, P>
+    where
+        M: 'static,
+    {
+        instrument::traced::<M, Self, _, _>("iter_selected_values", 0, || {
+            SelectedValueTableIterator {
+                underlying: self.database().db.iterator(IteratorMode::Start),
+                pred,
+                _mode: PhantomData,
+                _table: PhantomData,
+            }
+        })
+    }
+
+    /// Decode every entry in the table (verifying block checksums), collecting the entries that
+    /// can't be decoded instead of failing on the first one.
+    ///
+    /// Errors reading the database (including checksum mismatches) still end the scan.
+    fn verify(&self) -> Result<verify::VerifyReport<Self::Error>, Self::Error> {
+        let mut options = ReadOptions::default();
+        options.set_verify_checksums(true);
+        options.fill_cache(false);
+
+        let mut report = verify::VerifyReport::default();
+        let mut underlying = self.database().db.raw_iterator_opt(options);
+        underlying.seek_to_first();
+
+        while let (Some(key_bytes), Some(value_bytes)) = (underlying.key(), underlying.value()) {
+            report.scanned += 1;
+
+            if let Err(error) = Self::bytes_to_key(Cow::from(key_bytes))
+                .and_then(|_| Self::bytes_to_value(Cow::from(value_bytes)))
+            {
+                report.undecodable.push(verify::Undecodable {
+                    key_bytes: key_bytes.to_vec(),
+                    error,
+                });
+            }
+
+            underlying.next();
+        }
+
+        underlying.status().map_err(error::Error::from)?;
+
+        Ok(report)
+    }
+
+    fn lookup_key(&self, key: &Self::Key) -> Result<Option<Self::Value>, Self::Error> {
+        self.lookup_key_opt(key, &config::ReadConfig::default())
+    }
+
+    fn lookup_key_opt(
+        &self,
+        key: &Self::Key,
+        config: &config::ReadConfig,
+    ) -> Result<Option<Self::Value>, Self::Error> {
+        let key_bytes = Self::key_to_bytes(key)?;
+        let key_len = key_bytes.as_ref().len();
+        instrument::traced::<M, Self, _, _>("lookup", key_len, || {
+            self.database()
+                .db
+                .get_pinned_opt(key_bytes, &config.read_options())
+                .map_err(error::Error::from)?
+                .map_or(Ok(None), |value_bytes| {
+                    Self::bytes_to_value(Cow::from(value_bytes.as_ref())).map(Some)
                 })
         })
     }
-}
 
-pub enum IndexIterator<'a, M, T: Table<M>> {
-    ValidIndex {
-        underlying: DBIterator<'a>,
-        index_bytes: T::IndexBytes,
-        _mode: PhantomData<M>,
-        _table: PhantomData<T>,
-    },
-    InvalidIndex {
-        error: Option<T::Error>,
-    },
-}
+    /// Look up multiple keys at once, returning values in the same order as the keys.
+    fn multi_lookup(&self, keys: &[Self::Key]) -> Result<Vec<Option<Self::Value>>, Self::Error> {
+        let keys_bytes = EncodedKeys::new::<M, Self>(keys)?;
+        // The longest key is reported, for consistency with single-key operations.
+        let key_len = keys_bytes.iter().map(<[u8]>::len).max().unwrap_or(0);
 
-impl<'a, M: mode::Mode, T: Table<M>> Iterator for IndexIterator<'a, M, T> {
-    type Item = Result<(T::Key, T::Value), T::Error>;
+        instrument::traced::<M, Self, _, _>("multi_lookup", key_len, || {
+            self.database()
+                .db
+                .multi_get(keys_bytes.iter())
+                .into_iter()
+                .map(|result| {
+                    result
+                        .map_err(error::Error::from)?
+                        .map_or(Ok(None), |value_bytes| {
+                            Self::bytes_to_value(Cow::from(value_bytes)).map(Some)
+                        })
+                })
+                .collect()
+        })
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            IndexIterator::ValidIndex {
-                underlying,
-                index_bytes,
-                ..
-            } => underlying.next().and_then(|result| match result {
-                Ok((key_bytes, value_bytes)) => {
-                    if key_bytes.starts_with(index_bytes.as_ref()) {
-                        Some(
-                            T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
-                                T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
-                                    .map(|value| (key, value))
-                            }),
-                        )
-                    } else {
-                        None
-                    }
+    /// Look up multiple keys in batches of at most `chunk_size`, returning values in the same
+    /// order as the keys.
+    ///
+    /// Encoded keys are sorted before they're looked up, which allows RocksDB to skip sorting each
+    /// batch, and chunking bounds the number of values that are pinned at once. The chunk size
+    /// must be positive.
+    fn multi_lookup_sorted(
+        &self,
+        keys: &[Self::Key],
+        chunk_size: usize,
+    ) -> Result<Vec<Option<Self::Value>>, Self::Error> {
+        if chunk_size == 0 {
+            return Err(error::Error::InvalidConfiguration(
+                "Chunk size must be positive".to_string(),
+            )
+            .into());
+        }
+
+        let keys_bytes = EncodedKeys::new::<M, Self>(keys)?;
+        let mut indexed_keys_bytes = keys_bytes.iter().enumerate().collect::<Vec<_>>();
+
+        // The keys must be sorted in the table's key order.
+        match Self::comparator() {
+            Some(comparator) => {
+                indexed_keys_bytes.sort_by(|(_, a), (_, b)| comparator.compare(a, b))
+            }
+            None => indexed_keys_bytes.sort_by_key(|(_, key_bytes)| *key_bytes),
+        }
+
+        let database = self.database();
+        let mut values = Vec::with_capacity(keys.len());
+        values.resize_with(keys.len(), || None);
+
+        for chunk in indexed_keys_bytes.chunks(chunk_size) {
+            let results = database.db.batched_multi_get_cf(
+                database.default_cf(),
+                chunk.iter().map(|(_, key_bytes)| *key_bytes),
+                true,
+            );
+
+            for ((i, _), result) in chunk.iter().zip(results) {
+                if let Some(value_bytes) = result.map_err(error::Error::from)? {
+                    values[*i] = Some(Self::bytes_to_value(Cow::from(value_bytes.as_ref()))?);
                 }
-                Err(error) => Some(Err(T::Error::from(error.into()))),
-            }),
-            IndexIterator::InvalidIndex { error } => error.take().map(Err),
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Look up the encoded value for a key without copying it out of RocksDB.
+    ///
+    /// The returned slice dereferences to the value bytes, which can be decoded in place (for
+    /// example with `bytes_to_value(Cow::from(&*value_bytes))`), and keeps them pinned in the
+    /// block cache or memtable until it's dropped.
+    fn lookup_pinned(&self, key: &Self::Key) -> Result<Option<DBPinnableSlice<'_>>, Self::Error>
+    where
+        M: 'static,
+    {
+        let key_bytes = Self::key_to_bytes(key)?;
+        Ok(self
+            .database()
+            .db
+            .get_pinned(key_bytes)
+            .map_err(error::Error::from)?)
+    }
+
+    /// Check whether a key is present without decoding its value.
+    fn contains_key(&self, key: &Self::Key) -> Result<bool, Self::Error> {
+        let key_bytes = Self::key_to_bytes(key)?;
+
+        Ok(self
+            .database()
+            .db
+            .get_pinned(key_bytes)
+            .map_err(error::Error::from)?
+            .is_some())
+    }
+
+    /// Check whether a key may exist without doing a full lookup (using bloom filters and the
+    /// memtable, for example).
+    fn key_may_exist(&self, key: &Self::Key) -> Result<KeyMayExist<Self::Value>, Self::Error> {
+        let key_bytes = Self::key_to_bytes(key)?;
+        let database = self.database();
+
+        match database.db.key_may_exist_cf_opt_value(
+            database.default_cf(),
+            key_bytes,
+            &ReadOptions::default(),
+        ) {
+            (false, _) => Ok(KeyMayExist::Absent),
+            (true, None) => Ok(KeyMayExist::Maybe),
+            (true, Some(value_bytes)) => {
+                Self::bytes_to_value(Cow::from(value_bytes.as_ref())).map(KeyMayExist::Present)
+            }
+        }
+    }
+
+    /// The entry with the smallest key in the table.
+    fn first(&self) -> Result<Option<Entry<M, Self>>, Self::Error> {
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek_to_first();
+
+        current_entry::<M, Self>(&underlying)
+    }
+
+    /// The entry with the greatest key in the table.
+    fn last(&self) -> Result<Option<Entry<M, Self>>, Self::Error> {
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek_to_last();
+
+        current_entry::<M, Self>(&underlying)
+    }
+
+    /// Look up the entry with the greatest key less than or equal to the given key.
+    fn lookup_floor(&self, key: &Self::Key) -> Result<Option<Entry<M, Self>>, Self::Error> {
+        let key_bytes = Self::key_to_bytes(key)?;
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek_for_prev(key_bytes);
+
+        current_entry::<M, Self>(&underlying)
+    }
+
+    /// Look up the entry with the smallest key greater than or equal to the given key.
+    fn lookup_ceiling(&self, key: &Self::Key) -> Result<Option<Entry<M, Self>>, Self::Error> {
+        let key_bytes = Self::key_to_bytes(key)?;
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek(key_bytes);
+
+        current_entry::<M, Self>(&underlying)
+    }
+
+    fn lookup_index(&self, index: &Self::Index) -> IndexIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        self.lookup_index_opt(index, &config::ReadConfig::default())
+    }
+
+    fn lookup_index_opt(
+        &self,
+        index: &Self::Index,
+        config: &config::ReadConfig,
+    ) -> IndexIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        match Self::index_to_bytes(index) {
+            Ok(index_bytes) => {
+                let key_len = index_bytes.as_ref().len();
+
+                instrument::traced::<M, Self, _, _>("lookup_index", key_len, || {
+                    IndexIterator::ValidIndex {
+                        underlying: self.database().db.iterator_opt(
+                            IteratorMode::From(index_bytes.as_ref(), Direction::Forward),
+                            index_read_options(index_bytes.as_ref(), config.read_options()),
+                        ),
+                        index_bytes,
+                        _mode: PhantomData,
+                        _table: PhantomData,
+                    }
+                })
+            }
+            Err(error) => IndexIterator::InvalidIndex { error: Some(error) },
+        }
+    }
+
+    /// Look up all entries for an index in reverse key order.
+    fn lookup_index_rev(&self, index: &Self::Index) -> IndexIterator<'_, M, Self>
+    where
+        M: 'static,
+    {
+        match Self::index_to_bytes(index) {
+            Ok(index_bytes) => {
+                let key_len = index_bytes.as_ref().len();
+
+                instrument::traced::<M, Self, _, _>("lookup_index_rev", key_len, || {
+                    IndexIterator::ValidIndex {
+                        underlying: self.database().db.iterator_opt(
+                            IteratorMode::End,
+                            index_read_options(index_bytes.as_ref(), ReadOptions::default()),
+                        ),
+                        index_bytes,
+                        _mode: PhantomData,
+                        _table: PhantomData,
+                    }
+                })
+            }
+            Err(error) => IndexIterator::InvalidIndex { error: Some(error) },
+        }
+    }
+
+    fn lookup_index_selected_values<P: Fn(&Self::Key) -> bool>(
+        &self,
+        index: &Self::Index,
+        pred: P,
+    ) -> SelectedValueIndexIterator<'_, M, Self, P>
+    where
+        M: 'static,
+    {
+        match Self::index_to_bytes(index) {
+            Ok(index_bytes) => {
+                let key_len = index_bytes.as_ref().len();
+
+                instrument::traced::<M, Self, _, _>("lookup_index_selected_values", key_len, || {
+                    SelectedValueIndexIterator::ValidIndex {
+                        underlying: self.database().db.iterator_opt(
+                            IteratorMode::From(index_bytes.as_ref(), Direction::Forward),
+                            index_read_options(index_bytes.as_ref(), ReadOptions::default()),
+                        ),
+                        index_bytes,
+                        pred,
+                        _mode: PhantomData,
+                        _table: PhantomData,
+                    }
+                })
+            }
+            Err(error) => SelectedValueIndexIterator::InvalidIndex { error: Some(error) },
+        }
+    }
+
+    fn put(&self, key: &Self::Key, value: &Self::Value) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.put_opt(key, value, &config::WriteConfig::default())
+    }
+
+    fn put_opt(
+        &self,
+        key: &Self::Key,
+        value: &Self::Value,
+        config: &config::WriteConfig,
+    ) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let mut key_bytes = vec![];
+        Self::encode_key_into(key, &mut key_bytes)?;
+        let value_bytes = Self::value_to_bytes(value)?;
+        let key_len = key_bytes.len();
+        Ok(instrument::traced::<M, Self, _, _>("put", key_len, || {
+            self.database()
+                .db
+                .put_opt(key_bytes, value_bytes, &config.write_options())
+        })
+        .map_err(error::Error::from)?)
+    }
+
+    fn delete(&self, key: &Self::Key) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.delete_opt(key, &config::WriteConfig::default())
+    }
+
+    fn delete_opt(&self, key: &Self::Key, config: &config::WriteConfig) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let mut key_bytes = vec![];
+        Self::encode_key_into(key, &mut key_bytes)?;
+        let key_len = key_bytes.len();
+        Ok(instrument::traced::<M, Self, _, _>("delete", key_len, || {
+            self.database()
+                .db
+                .delete_opt(key_bytes, &config.write_options())
+        })
+        .map_err(error::Error::from)?)
+    }
+
+    /// Merge a value into the entry for a key (this requires a merge operator to be configured).
+    fn merge(&self, key: &Self::Key, value: &Self::Value) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.merge_opt(key, value, &config::WriteConfig::default())
+    }
+
+    fn merge_opt(
+        &self,
+        key: &Self::Key,
+        value: &Self::Value,
+        config: &config::WriteConfig,
+    ) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let mut key_bytes = vec![];
+        Self::encode_key_into(key, &mut key_bytes)?;
+        let value_bytes = Self::value_to_bytes(value)?;
+        let key_len = key_bytes.len();
+        Ok(instrument::traced::<M, Self, _, _>("merge", key_len, || {
+            self.database()
+                .db
+                .merge_opt(key_bytes, value_bytes, &config.write_options())
+        })
+        .map_err(error::Error::from)?)
+    }
+
+    /// Add a delta to a counter (this requires an addition merge operator to be configured, such as
+    /// [`merge::MergeOperator::add_u64`]).
+    fn increment(&self, key: &Self::Key, delta: &Self::Value) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.merge(key, delta)
+    }
+
+    /// Delete the entries that have expired according to a retention policy, given a function
+    /// that extracts timestamps from keys (entries without a timestamp are kept).
+    ///
+    /// Deletions are written in batches, and the progress function is called after each batch.
+    fn apply_retention<
+        F: Fn(&Self::Key) -> Option<SystemTime>,
+        P: FnMut(&retention::RetentionProgress),
+    >(
+        &self,
+        retention: &retention::Retention,
+        timestamp: F,
+        mut progress: P,
+    ) -> Result<retention::RetentionProgress, Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let db = &self.database().db;
+        let now = SystemTime::now();
+        let mut result = retention::RetentionProgress::default();
+        let mut batch = WriteBatch::default();
+
+        let mut underlying = db.raw_iterator();
+        underlying.seek_to_first();
+
+        while let Some(key_bytes) = underlying.key() {
+            let key = Self::bytes_to_key(Cow::from(key_bytes))?;
+            result.scanned += 1;
+
+            if timestamp(&key).is_some_and(|timestamp| retention.is_expired(timestamp, now)) {
+                batch.delete(key_bytes);
+                result.removed += 1;
+
+                if batch.len() >= RETENTION_BATCH_SIZE {
+                    db.write(std::mem::take(&mut batch))
+                        .map_err(error::Error::from)?;
+                    progress(&result);
+                }
+            }
+
+            underlying.next();
+        }
+
+        underlying.status().map_err(error::Error::from)?;
+
+        if !batch.is_empty() {
+            db.write(batch).map_err(error::Error::from)?;
+        }
+
+        progress(&result);
+
+        Ok(result)
+    }
+
+    /// The version of the last migration applied to the table (if any).
+    fn migration_version(&self) -> Result<Option<u32>, Self::Error> {
+        Ok(migration::version(self)?)
+    }
+
+    /// Apply any migrations with versions newer than the table's current version, in order of
+    /// version, and return the versions that were applied.
+    ///
+    /// Each migration's changes are written in a single batch together with its version, so a
+    /// migration is either applied completely or not at all. Versions are stored with the schema
+    /// metadata, so this fails unless the table stores its schema fingerprint.
+    fn migrate(
+        &self,
+        migrations: &[&dyn migration::Migration<M, Self>],
+    ) -> Result<Vec<u32>, Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        migration::apply(self, migrations)
+    }
+
+    /// Remove every entry with a single range deletion, and compact the table, returning the
+    /// number of bytes of SST files reclaimed.
+    ///
+    /// The deleted range runs from the first to the last key when the table is read, so entries
+    /// written concurrently outside that range aren't removed.
+    fn truncate(&self) -> Result<u64, Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let db = &self.database().db;
+        let sst_files_size = || {
+            db.property_int_value(TOTAL_SST_FILES_SIZE_PROPERTY)
+                .map(Option::unwrap_or_default)
+        };
+
+        let mut underlying = db.raw_iterator();
+        underlying.seek_to_first();
+        let first = underlying.key().map(<[u8]>::to_vec);
+        underlying.seek_to_last();
+        let last = underlying.key().map(<[u8]>::to_vec);
+        underlying.status().map_err(error::Error::from)?;
+        drop(underlying);
+
+        db.flush().map_err(error::Error::from)?;
+        let before = sst_files_size().map_err(error::Error::from)?;
+
+        if let (Some(first), Some(last)) = (first, last) {
+            // The last key is deleted separately, since its successor depends on the comparator.
+            let mut batch = WriteBatch::default();
+            batch.delete_range(first.as_slice(), last.as_slice());
+            batch.delete(last);
+            db.write(batch).map_err(error::Error::from)?;
+            db.flush().map_err(error::Error::from)?;
+        }
+
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+
+        Ok(before.saturating_sub(sst_files_size().map_err(error::Error::from)?))
+    }
+
+    /// The sequence number of the most recent write.
+    fn latest_sequence_number(&self) -> u64 {
+        self.database().db.latest_sequence_number()
+    }
+
+    /// Iterate over the puts and deletions in write-ahead log batches after the given sequence
+    /// number (see [`wal::WalOperation`] for the operations that are included).
+    ///
+    /// Log files are normally deleted once their contents are flushed, so tables that are read
+    /// this way should set `set_wal_ttl_seconds` or `set_wal_size_limit_mb` to keep them.
+    ///
+    /// Writes that aren't logged (such as writes made with [`config::WriteConfig::bulk`] and
+    /// entries ingested from SST files) aren't included, and if they use sequence numbers, the
+    /// iteration silently ends at the first one.
+    fn updates_since(&self, sequence: u64) -> Result<wal::WalIterator<'_, M, Self>, Self::Error> {
+        Ok(wal::WalIterator::new(
+            self.database()
+                .db
+                .get_updates_since(sequence)
+                .map_err(error::Error::from)?,
+        ))
+    }
+
+    /// Write the current values of the keys that were put or deleted after the given sequence
+    /// number, in the format described in [`changes`].
+    ///
+    /// This uses the write-ahead log (see [`Table::updates_since`]), so it fails with
+    /// [`error::Error::IncompleteLog`] if any later write wasn't logged (for example writes made
+    /// with [`config::WriteConfig::bulk`]) or is in a batch that can't be decoded (merges, range
+    /// deletions such as those made by [`Table::truncate`], and writes to other column families,
+    /// such as audit records). Entries ingested from SST files that don't overlap existing keys
+    /// aren't logged and don't use sequence numbers, so they're never included.
+    fn export_since<W: std::io::Write>(
+        &self,
+        sequence: u64,
+        writer: W,
+    ) -> Result<changes::ChangeSummary, Self::Error> {
+        Ok(changes::export_since(self, sequence, writer)?)
+    }
+
+    /// Apply changes written by [`Table::export_since`] (for a table with the same encoding).
+    ///
+    /// Changes are written in batches, so if the import fails, some changes may have been applied.
+    fn import_changes<R: std::io::Read>(
+        &self,
+        reader: R,
+    ) -> Result<changes::ChangeSummary, Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        Ok(changes::import_changes(self, reader)?)
+    }
+
+    /// The current state of the stall-related properties of the database.
+    fn stall_state(&self) -> Result<stall::StallState, Self::Error> {
+        Ok(stall::StallState::read(self.database())?)
+    }
+
+    /// Write entries in batches, pausing before each batch while the database is stalling.
+    fn put_all<I: IntoIterator<Item = Entry<M, Self>>>(
+        &self,
+        entries: I,
+        backpressure: &stall::Backpressure,
+    ) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let db = &self.database().db;
+        let mut batch = WriteBatch::default();
+        let mut key_buffer = vec![];
+
+        for (key, value) in entries {
+            key_buffer.clear();
+            Self::encode_key_into(&key, &mut key_buffer)?;
+            batch.put(&key_buffer, Self::value_to_bytes(&value)?);
+
+            if batch.len() >= PUT_ALL_BATCH_SIZE {
+                backpressure.wait(self.database())?;
+                db.write(std::mem::take(&mut batch))
+                    .map_err(error::Error::from)?;
+            }
+        }
+
+        if !batch.is_empty() {
+            backpressure.wait(self.database())?;
+            db.write(batch).map_err(error::Error::from)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy every entry into another table, transforming each entry (entries are skipped if the
+    /// transform returns `None`), and return the number of entries written.
+    fn copy_into<N, U, F>(&self, target: &U, mut transform: F) -> Result<u64, Self::Error>
+    where
+        M: mode::Mode,
+        N: mode::IsWriteable,
+        U: Table<N>,
+        Self::Error: From<U::Error>,
+        F: FnMut(Self::Key, Self::Value) -> Option<Entry<N, U>>,
+    {
+        let db = &target.database().db;
+        let mut batch = WriteBatch::default();
+        let mut key_buffer = vec![];
+        let mut count = 0;
+
+        for result in self.iter() {
+            let (key, value) = result?;
+
+            if let Some((key, value)) = transform(key, value) {
+                key_buffer.clear();
+                U::encode_key_into(&key, &mut key_buffer)?;
+                batch.put(&key_buffer, U::value_to_bytes(&value)?);
+                count += 1;
+
+                if batch.len() >= PUT_ALL_BATCH_SIZE {
+                    db.write(std::mem::take(&mut batch))
+                        .map_err(error::Error::from)?;
+                }
+            }
+        }
+
+        if !batch.is_empty() {
+            db.write(batch).map_err(error::Error::from)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Copy every entry into another table (see [`Table::copy_into`]) and then truncate this
+    /// table, returning the number of entries written.
+    fn move_into<N, U, F>(&self, target: &U, transform: F) -> Result<u64, Self::Error>
+    where
+        M: mode::IsWriteable,
+        N: mode::IsWriteable,
+        U: Table<N>,
+        Self::Error: From<U::Error>,
+        F: FnMut(Self::Key, Self::Value) -> Option<Entry<N, U>>,
+    {
+        let count = self.copy_into(target, transform)?;
+        self.truncate()?;
+
+        Ok(count)
+    }
+
+    /// Export every entry to another table (for example one of the same type in another database),
+    /// returning the number of entries written.
+    ///
+    /// Entries are copied without decoding them, so the tables must use the same key and value
+    /// encodings. If neither table has a custom comparator, entries are ingested as SST files
+    /// (written in the target database's directory).
+    fn export_into<N, U>(&self, target: &U) -> Result<u64, Self::Error>
+    where
+        N: mode::IsWriteable,
+        U: Table<N>,
+    {
+        let target_database = target.database();
+        let mut underlying = self.database().db.raw_iterator();
+        underlying.seek_to_first();
+
+        if Self::comparator().is_none() && U::comparator().is_none() {
+            let directory =
+                tempfile::tempdir_in(target_database.db.path()).map_err(error::Error::from)?;
+
+            let result =
+                sst::write_raw_files(&target_database.options, directory.path(), &mut underlying)
+                    .and_then(|(paths, count)| {
+                        ingest_moved_files(target_database, paths)?;
+                        Ok(count)
+                    });
+            let cleanup = directory.close();
+
+            let count = result?;
+            cleanup.map_err(error::Error::from)?;
+
+            Ok(count)
+        } else {
+            let mut batch = WriteBatch::default();
+            let mut count = 0;
+
+            while let (Some(key_bytes), Some(value_bytes)) = (underlying.key(), underlying.value())
+            {
+                batch.put(key_bytes, value_bytes);
+                count += 1;
+
+                if batch.len() >= PUT_ALL_BATCH_SIZE {
+                    target_database
+                        .db
+                        .write(std::mem::take(&mut batch))
+                        .map_err(error::Error::from)?;
+                }
+
+                underlying.next();
+            }
+
+            underlying.status().map_err(error::Error::from)?;
+
+            if !batch.is_empty() {
+                target_database
+                    .db
+                    .write(batch)
+                    .map_err(error::Error::from)?;
+            }
+
+            Ok(count)
+        }
+    }
+
+    /// Create a writer for an SST file that uses this table's options.
+    fn sst_writer<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<sst::SstWriter<'_, M, Self>, Self::Error>
+    where
+        M: 'static,
+    {
+        sst::SstWriter::create(&self.database().options, path)
+    }
+
+    /// Ingest SST files (for example, files written by [`Table::sst_writer`]) into the table.
+    fn ingest<P: AsRef<Path>>(&self, paths: Vec<P>) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let database = self.database();
+
+        Ok(database
+            .db
+            .ingest_external_file_cf(database.default_cf(), paths)
+            .map_err(error::Error::from)?)
+    }
+
+    /// Ingest entries that are sorted in the table's key order (see [`Table::comparator`]), by
+    /// writing them to temporary SST files in the database directory.
+    ///
+    /// If the keys aren't strictly increasing, nothing is ingested and the first out-of-order key
+    /// is returned in an [`error::Error::OutOfOrderKey`] error.
+    fn ingest_sorted<I: IntoIterator<Item = Entry<M, Self>>>(
+        &self,
+        entries: I,
+    ) -> Result<(), Self::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let database = self.database();
+        let directory = tempfile::tempdir_in(database.db.path()).map_err(error::Error::from)?;
+
+        let result = sst::write_files::<M, Self, _>(&database.options, directory.path(), entries)
+            .and_then(|paths| Ok(ingest_moved_files(database, paths)?));
+        let cleanup = directory.close();
+
+        result?;
+        Ok(cleanup.map_err(error::Error::from)?)
+    }
+
+    fn catch_up_with_primary(&self) -> Result<(), Self::Error>
+    where
+        M: mode::IsSecondary,
+    {
+        Ok(self
+            .database()
+            .db
+            .try_catch_up_with_primary()
+            .map_err(error::Error::from)?)
+    }
+}
+
+/// Encoded key bounds, with an inclusive lower bound and an exclusive upper bound.
+struct KeyRange {
+    lower: Option<Vec<u8>>,
+    upper: Option<Vec<u8>>,
+}
+
+impl KeyRange {
+    fn new<M, T: Table<M>>(start: Bound<&T::Key>, end: Bound<&T::Key>) -> Result<Self, T::Error> {
+        let lower = match start {
+            Bound::Included(key) => Some(T::key_to_bytes(key)?.as_ref().to_vec()),
+            Bound::Excluded(key) => Some(key_successor(T::key_to_bytes(key)?.as_ref())),
+            Bound::Unbounded => None,
+        };
+
+        let upper = match end {
+            Bound::Included(key) => Some(key_successor(T::key_to_bytes(key)?.as_ref())),
+            Bound::Excluded(key) => Some(T::key_to_bytes(key)?.as_ref().to_vec()),
+            Bound::Unbounded => None,
+        };
+
+        Ok(Self { lower, upper })
+    }
+
+    /// Whether the range overlaps the inclusive range between the given keys (with missing keys
+    /// treated as unbounded).
+    fn overlaps(&self, smallest: Option<&[u8]>, largest: Option<&[u8]>) -> bool {
+        let starts_before_upper = match (&self.upper, smallest) {
+            (Some(upper), Some(smallest)) => smallest < upper.as_slice(),
+            _ => true,
+        };
+
+        let ends_after_lower = match (&self.lower, largest) {
+            (Some(lower), Some(largest)) => largest >= lower.as_slice(),
+            _ => true,
+        };
+
+        starts_before_upper && ends_after_lower
+    }
+}
+
+/// Decode the entry at the current position of a raw iterator (if it is valid).
+fn current_entry<M, T: Table<M>>(
+    underlying: &DBRawIterator<'_>,
+) -> Result<Option<Entry<M, T>>, T::Error> {
+    match underlying.item() {
+        Some((key_bytes, value_bytes)) => {
+            let key = T::bytes_to_key(Cow::from(key_bytes))?;
+            let value = T::bytes_to_value(Cow::from(value_bytes))?;
+
+            Ok(Some((key, value)))
+        }
+        None => {
+            underlying.status().map_err(error::Error::from)?;
+
+            Ok(None)
+        }
+    }
+}
+
+/// Encoded keys stored contiguously in a single buffer.
+struct EncodedKeys {
+    buffer: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl EncodedKeys {
+    fn new<M, T: Table<M>>(keys: &[T::Key]) -> Result<Self, T::Error> {
+        let mut buffer = vec![];
+        let mut ends = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            T::encode_key_into(key, &mut buffer)?;
+            ends.push(buffer.len());
+        }
+
+        Ok(Self { buffer, ends })
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        let starts = std::iter::once(0).chain(self.ends.iter().copied());
+
+        starts
+            .zip(self.ends.iter().copied())
+            .map(|(start, end)| &self.buffer[start..end])
+    }
+}
+
+/// Ingest SST files into the default column family, moving them into the database.
+fn ingest_moved_files<M>(database: &Database<M>, paths: Vec<PathBuf>) -> Result<(), error::Error> {
+    if !paths.is_empty() {
+        let mut ingest_options = IngestExternalFileOptions::default();
+        ingest_options.set_move_files(true);
+
+        database
+            .db
+            .ingest_external_file_cf_opts(database.default_cf(), &ingest_options, paths)?;
+    }
+
+    Ok(())
+}
+
+/// The smallest byte string that is greater than the given one.
+fn key_successor(key: &[u8]) -> Vec<u8> {
+    let mut successor = Vec::with_capacity(key.len() + 1);
+    successor.extend_from_slice(key);
+    successor.push(0);
+    successor
+}
+
+/// Read options that bound iteration to keys starting with the given index prefix, so that scans
+/// terminate correctly even without a prefix extractor.
+fn index_read_options(index_bytes: &[u8], mut options: ReadOptions) -> ReadOptions {
+    options.set_iterate_lower_bound(index_bytes);
+
+    if let Some(upper_bound) = prefix_upper_bound(index_bytes) {
+        options.set_iterate_upper_bound(upper_bound);
+    }
+
+    options
+}
+
+/// The smallest byte string that is greater than every string with the given prefix (if one
+/// exists).
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper_bound = prefix.to_vec();
+
+    while let Some(last) = upper_bound.pop() {
+        if last < u8::MAX {
+            upper_bound.push(last + 1);
+            return Some(upper_bound);
+        }
+    }
+
+    None
+}
+
+pub struct TableIterator<'a, M, T> {
+    underlying: DBIterator<'a>,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: Table<M>> Iterator for TableIterator<'a, M, T> {
+    type Item = Result<(T::Key, T::Value), T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.underlying.next().map(|result| {
+            result
+                .map_err(|error| T::Error::from(error.into()))
+                .and_then(|(key_bytes, value_bytes)| {
+                    T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
+                        T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
+                            .map(|value| (key, value))
+                    })
+                })
+        })
+    }
+}
+
+/// Iterates over keys only (useful if values are large or expensive to decode).
+pub struct KeyIterator<'a, M, T> {
+    underlying: DBRawIterator<'a>,
+    done: bool,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: Table<M>> Iterator for KeyIterator<'a, M, T> {
+    type Item = Result<T::Key, T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else if let Some(key_bytes) = self.underlying.key() {
+            let result = T::bytes_to_key(Cow::from(key_bytes));
+            self.underlying.next();
+            Some(result)
+        } else {
+            self.done = true;
+            self.underlying
+                .status()
+                .err()
+                .map(|error| Err(T::Error::from(error.into())))
+        }
+    }
+}
+
+/// Iterates over entries appended after the last key (see [`Table::tail`]).
+pub struct TailIterator<'a, M, T> {
+    underlying: DBRawIterator<'a>,
+    last_key_bytes: Option<Vec<u8>>,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: Table<M>> Iterator for TailIterator<'a, M, T> {
+    type Item = Result<(T::Key, T::Value), T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.underlying.valid() {
+            self.underlying.next();
+        }
+
+        // A tailing iterator only sees new writes after a seek.
+        if !self.underlying.valid() {
+            match &self.last_key_bytes {
+                Some(last_key_bytes) => self.underlying.seek(key_successor(last_key_bytes)),
+                None => self.underlying.seek_to_first(),
+            }
+        }
+
+        match self.underlying.item() {
+            Some((key_bytes, value_bytes)) => {
+                self.last_key_bytes = Some(key_bytes.to_vec());
+
+                Some(T::bytes_to_key(Cow::from(key_bytes)).and_then(|key| {
+                    T::bytes_to_value(Cow::from(value_bytes)).map(|value| (key, value))
+                }))
+            }
+            None => self
+                .underlying
+                .status()
+                .err()
+                .map(|error| Err(T::Error::from(error.into()))),
+        }
+    }
+}
+
+/// Allows selection of values to decode (if for example this is expensive).
+pub struct SelectedValueTableIterator<'a, M, T, P> {
+    underlying: DBIterator<'a>,
+    pred: P,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M: mode::Mode, T: Table<M>, P: Fn(&T::Key) -> bool> Iterator
+    for SelectedValueTableIterator<'a, M, T, P>
+{
+    type Item = Result<(T::Key, Option<T::Value>), T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.underlying.next().map(|result| {
+            result
+                .map_err(|error| T::Error::from(error.into()))
+                .and_then(|(key_bytes, value_bytes)| {
+                    T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
+                        if (self.pred)(&key) {
+                            T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
+                                .map(|value| (key, Some(value)))
+                        } else {
+                            Ok((key, None))
+                        }
+                    })
+                })
+        })
+    }
+}
+
+pub enum IndexIterator<'a, M, T: Table<M>> {
+    ValidIndex {
+        underlying: DBIterator<'a>,
+        index_bytes: T::IndexBytes,
+        _mode: PhantomData<M>,
+        _table: PhantomData<T>,
+    },
+    InvalidIndex {
+        error: Option<T::Error>,
+    },
+}
+
+impl<'a, M: mode::Mode, T: Table<M>> Iterator for IndexIterator<'a, M, T> {
+    type Item = Result<(T::Key, T::Value), T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IndexIterator::ValidIndex {
+                underlying,
+                index_bytes,
+                ..
+            } => underlying.next().and_then(|result| match result {
+                Ok((key_bytes, value_bytes)) => {
+                    if key_bytes.starts_with(index_bytes.as_ref()) {
+                        Some(
+                            T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
+                                T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
+                                    .map(|value| (key, value))
+                            }),
+                        )
+                    } else {
+                        None
+                    }
+                }
+                Err(error) => Some(Err(T::Error::from(error.into()))),
+            }),
+            IndexIterator::InvalidIndex { error } => error.take().map(Err),
+        }
+    }
+}
+
+/// Allows selection of values to decode (if for example this is expensive).
+pub enum SelectedValueIndexIterator<'a, M, T: Table<M>, P> {
+    ValidIndex {
+        underlying: DBIterator<'a>,
+        index_bytes: T::IndexBytes,
+        pred: P,
+        _mode: PhantomData<M>,
+        _table: PhantomData<T>,
+    },
+    InvalidIndex {
+        error: Option<T::Error>,
+    },
+}
+
+impl<'a, M: mode::Mode, T: Table<M>, P: Fn(&T::Key) -> bool> Iterator
+    for SelectedValueIndexIterator<'a, M, T, P>
+{
+    type Item = Result<(T::Key, Option<T::Value>), T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SelectedValueIndexIterator::ValidIndex {
+                underlying,
+                index_bytes,
+                pred,
+                ..
+            } => underlying.next().and_then(|result| match result {
+                Ok((key_bytes, value_bytes)) => {
+                    if key_bytes.starts_with(index_bytes.as_ref()) {
+                        Some(
+                            T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
+                                if (pred)(&key) {
+                                    T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
+                                        .map(|value| (key, Some(value)))
+                                } else {
+                                    Ok((key, None))
+                                }
+                            }),
+                        )
+                    } else {
+                        None
+                    }
+                }
+                Err(error) => Some(Err(T::Error::from(error.into()))),
+            }),
+            SelectedValueIndexIterator::InvalidIndex { error } => error.take().map(Err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(thiserror::Error, Debug)]
+    pub enum Error {
+        #[error("RocksDb table error")]
+        RocksDbTable(#[from] error::Error),
+        #[error("String encoding error")]
+        Utf8(#[from] std::str::Utf8Error),
+    }
+
+    struct Dictionary<M> {
+        database: Database<M>,
+    }
+
+    impl<M: mode::Mode> Table<M> for Dictionary<M> {
+        type Counts = usize;
+        type Error = Error;
+        type Key = String;
+        type KeyBytes = Vec<u8>;
+        type Value = u64;
+        type ValueBytes = [u8; 8];
+        type Index = String;
+        type IndexBytes = Vec<u8>;
+
+        fn database(&self) -> &Database<M> {
+            &self.database
+        }
+
+        fn from_database(database: Database<M>) -> Self {
+            Self { database }
+        }
+
+        fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
+            Ok(key.as_bytes().to_vec())
+        }
+
+        fn encode_key_into(key: &Self::Key, buffer: &mut Vec<u8>) -> Result<(), Self::Error> {
+            buffer.extend_from_slice(key.as_bytes());
+            Ok(())
+        }
+
+        fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+            Ok(value.to_be_bytes())
+        }
+
+        fn index_to_bytes(index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
+            Ok(index.as_bytes().to_vec())
+        }
+
+        fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
+            Ok(std::str::from_utf8(bytes.as_ref())?.to_string())
+        }
+
+        fn bytes_to_value(bytes: Cow<[u8]>) -> Result<Self::Value, Self::Error> {
+            let mut reader = codec::ByteReader::for_value(&bytes);
+            let value = reader.read_u64_be()?;
+            reader.finish()?;
+
+            Ok(value)
+        }
+
+        fn get_counts(&self) -> Result<Self::Counts, Error> {
+            let mut count = 0;
+
+            for result in self.iter() {
+                result?;
+                count += 1;
+            }
+
+            Ok(count)
+        }
+    }
+
+    /// Define a table with the same encodings as `Dictionary` and the given configuration hooks.
+    macro_rules! dictionary_variant {
+        ($name:ident { $($hooks:tt)* }) => {
+            struct $name<M> {
+                database: Database<M>,
+            }
+
+            impl<M: mode::Mode> Table<M> for $name<M> {
+                type Counts = ();
+                type Error = Error;
+                type Key = String;
+                type KeyBytes = Vec<u8>;
+                type Value = u64;
+                type ValueBytes = [u8; 8];
+                type Index = String;
+                type IndexBytes = Vec<u8>;
+
+                fn database(&self) -> &Database<M> {
+                    &self.database
+                }
+
+                fn from_database(database: Database<M>) -> Self {
+                    Self { database }
+                }
+
+                fn get_counts(&self) -> Result<Self::Counts, Self::Error> {
+                    Ok(())
+                }
+
+                fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
+                    Dictionary::<M>::key_to_bytes(key)
+                }
+
+                fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+                    Dictionary::<M>::value_to_bytes(value)
+                }
+
+                fn index_to_bytes(index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
+                    Dictionary::<M>::index_to_bytes(index)
+                }
+
+                fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
+                    Dictionary::<M>::bytes_to_key(bytes)
+                }
+
+                fn bytes_to_value(bytes: Cow<[u8]>) -> Result<Self::Value, Self::Error> {
+                    Dictionary::<M>::bytes_to_value(bytes)
+                }
+
+                $($hooks)*
+            }
+        };
+    }
+
+    /// Sums counts in merges.
+    macro_rules! sum_merge {
+        ($name:ident) => {
+            impl<M: mode::Mode> merge::TypedMerge<M> for $name<M> {
+                fn merge_values(existing: Option<u64>, operands: impl Iterator<Item = u64>) -> u64 {
+                    existing.unwrap_or_default() + operands.sum::<u64>()
+                }
+            }
+        };
+    }
+
+    dictionary_variant!(Counts {
+        fn merge_operator() -> Option<merge::MergeOperator> {
+            Some(merge::MergeOperator::typed::<M, Self>("sum"))
+        }
+    });
+
+    sum_merge!(Counts);
+
+    dictionary_variant!(NonZeroCounts {
+        fn compaction_filter() -> Option<compaction::CompactionFilter> {
+            // Zero counts are removed during compaction.
+            Some(compaction::CompactionFilter::typed::<M, Self, _>(
+                "remove_zero",
+                |_, value| {
+                    if *value == 0 {
+                        compaction::FilterDecision::Remove
+                    } else {
+                        compaction::FilterDecision::Keep
+                    }
+                },
+            ))
+        }
+    });
+
+    dictionary_variant!(FilteredDictionary {
+        fn block_config() -> Option<block::BlockConfig> {
+            Some(block::BlockConfig::point_lookup())
+        }
+    });
+
+    dictionary_variant!(AuditedCounts {
+        fn merge_operator() -> Option<merge::MergeOperator> {
+            Some(merge::MergeOperator::typed::<M, Self>("sum"))
+        }
+
+        fn audited() -> bool {
+            true
+        }
+    });
+
+    sum_merge!(AuditedCounts);
+
+    dictionary_variant!(FingerprintedDictionary {
+        fn schema_fingerprint() -> bool {
+            true
+        }
+    });
+
+    dictionary_variant!(FingerprintedCounts {
+        fn merge_operator() -> Option<merge::MergeOperator> {
+            Some(merge::MergeOperator::typed::<M, Self>("sum"))
+        }
+
+        fn schema_fingerprint() -> bool {
+            true
+        }
+    });
+
+    sum_merge!(FingerprintedCounts);
+
+    dictionary_variant!(ExpiringDictionary {
+        fn default_ttl() -> Option<Duration> {
+            Some(Duration::from_secs(3600))
+        }
+    });
+
+    dictionary_variant!(ReversedDictionary {
+        fn comparator() -> Option<comparator::Comparator> {
+            Some(comparator::Comparator::new("reverse", |a, b| b.cmp(a)))
+        }
+    });
+
+    dictionary_variant!(SoftDeleteDictionary {
+        fn soft_delete() -> Option<soft_delete::SoftDelete> {
+            Some(soft_delete::SoftDelete {
+                grace_period: Duration::ZERO,
+            })
+        }
+    });
+
+    fn contents() -> Vec<(String, u64)> {
+        vec![
+            ("bar", 1000),
+            ("baz", 98765),
+            ("foo", 1),
+            ("abc", 23),
+            ("qux", 0),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+    }
+
+    #[test]
+    fn lookup_key() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+        assert_eq!(
+            dictionary.lookup_key(&"bar".to_string()).unwrap(),
+            Some(1000)
+        );
+        assert_eq!(dictionary.lookup_key(&"XYZ".to_string()).unwrap(), None);
+    }
+
+    #[test]
+    fn contains_key() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert!(dictionary.contains_key(&"foo".to_string()).unwrap());
+        assert!(!dictionary.contains_key(&"XYZ".to_string()).unwrap());
+    }
+
+    #[test]
+    fn first_and_last() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        assert_eq!(dictionary.first().unwrap(), None);
+        assert_eq!(dictionary.last().unwrap(), None);
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(dictionary.first().unwrap(), Some(("abc".to_string(), 23)));
+        assert_eq!(dictionary.last().unwrap(), Some(("qux".to_string(), 0)));
+    }
+
+    #[test]
+    fn lookup_floor_and_ceiling() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(
+            dictionary.lookup_floor(&"bb".to_string()).unwrap(),
+            Some(("baz".to_string(), 98765))
+        );
+        assert_eq!(
+            dictionary.lookup_floor(&"foo".to_string()).unwrap(),
+            Some(("foo".to_string(), 1))
+        );
+        assert_eq!(dictionary.lookup_floor(&"a".to_string()).unwrap(), None);
+
+        assert_eq!(
+            dictionary.lookup_ceiling(&"bb".to_string()).unwrap(),
+            Some(("foo".to_string(), 1))
+        );
+        assert_eq!(dictionary.lookup_ceiling(&"xyz".to_string()).unwrap(), None);
+    }
+
+    #[test]
+    fn key_may_exist() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(
+            dictionary.key_may_exist(&"foo".to_string()).unwrap(),
+            KeyMayExist::Present(1)
+        );
+        assert_eq!(
+            dictionary.key_may_exist(&"XYZ".to_string()).unwrap(),
+            KeyMayExist::Absent
+        );
+    }
+
+    #[test]
+    fn lookup_index() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(
+            &dictionary
+                .lookup_index(&"ba".to_string())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            &contents()[0..2].to_vec()
+        );
+    }
+
+    #[test]
+    fn iter() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let mut expected = contents();
+        expected.sort();
+
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn lookup_index_rev() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let mut expected = contents()[0..2].to_vec();
+        expected.reverse();
+
+        assert_eq!(
+            dictionary
+                .lookup_index_rev(&"ba".to_string())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn iter_rev() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let mut expected = contents();
+        expected.sort();
+        expected.reverse();
+
+        assert_eq!(
+            dictionary
+                .iter_rev()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn prefix_upper_bound() {
+        assert_eq!(super::prefix_upper_bound(b"ab"), Some(b"ac".to_vec()));
+        assert_eq!(super::prefix_upper_bound(b"a\xff"), Some(b"b".to_vec()));
+        assert_eq!(super::prefix_upper_bound(b"\xff\xff"), None);
+    }
+
+    #[test]
+    fn iter_range() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let bar = "bar".to_string();
+        let foo = "foo".to_string();
+
+        let included = dictionary
+            .iter_range(Bound::Included(&bar), Bound::Included(&foo))
+            .unwrap()
+            .map(|result| result.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let excluded = dictionary
+            .iter_range(Bound::Excluded(&bar), Bound::Excluded(&foo))
+            .unwrap()
+            .map(|result| result.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(included, vec!["bar", "baz", "foo"]);
+        assert_eq!(excluded, vec!["baz"]);
+    }
+
+    #[test]
+    fn approximate_size() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        dictionary.database().db.flush().unwrap();
+
+        let a = "a".to_string();
+        let b = "b".to_string();
+        let x = "x".to_string();
+
+        assert!(
+            dictionary
+                .approximate_size(Bound::Included(&a), Bound::Excluded(&b))
+                .unwrap()
+                > 0
+        );
+        assert_eq!(
+            dictionary
+                .approximate_size(Bound::Included(&x), Bound::Unbounded)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn iter_from() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let forward = dictionary
+            .iter_from(&"bb".to_string(), Direction::Forward)
+            .unwrap()
+            .map(|result| result.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let reverse = dictionary
+            .iter_from(&"bb".to_string(), Direction::Reverse)
+            .unwrap()
+            .map(|result| result.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(forward, vec!["foo", "qux"]);
+        assert_eq!(reverse, vec!["baz", "bar", "abc"]);
+    }
+
+    #[test]
+    fn iter_page() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let mut expected = contents();
+        expected.sort();
+
+        let first = dictionary.iter_page(None, 2).unwrap();
+        let second = dictionary.iter_page(first.next.as_ref(), 2).unwrap();
+        let third = dictionary.iter_page(second.next.as_ref(), 2).unwrap();
+
+        assert_eq!(first.entries, expected[0..2]);
+        assert_eq!(second.entries, expected[2..4]);
+        assert_eq!(third.entries, expected[4..]);
+        assert_eq!(third.next, None);
+
+        assert!(matches!(
+            dictionary.iter_page(first.next.as_ref(), 0),
+            Err(Error::RocksDbTable(error::Error::InvalidConfiguration(_)))
+        ));
+    }
+
+    #[test]
+    fn tail() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        dictionary.put(&"abc".to_string(), &1).unwrap();
+
+        let mut tail = dictionary.tail();
+        assert!(tail.next().is_none());
+
+        dictionary.put(&"def".to_string(), &2).unwrap();
+        dictionary.put(&"ghi".to_string(), &3).unwrap();
+
+        assert_eq!(tail.next().unwrap().unwrap(), ("def".to_string(), 2));
+        assert_eq!(tail.next().unwrap().unwrap(), ("ghi".to_string(), 3));
+        assert!(tail.next().is_none());
+
+        dictionary.put(&"jkl".to_string(), &4).unwrap();
+
+        assert_eq!(tail.next().unwrap().unwrap(), ("jkl".to_string(), 4));
+    }
+
+    #[test]
+    fn iter_opt() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let mut expected = contents();
+        expected.sort();
+
+        assert_eq!(
+            dictionary
+                .iter_opt(&config::ReadConfig::scan())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn put_opt() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        let durable = config::WriteConfig::durable();
+        let bulk = config::WriteConfig::bulk();
+
+        assert!(durable.sync && !durable.disable_wal);
+        assert!(bulk.disable_wal && bulk.low_pri && !bulk.sync);
+
+        dictionary
+            .put_opt(&"foo".to_string(), &1, &durable)
+            .unwrap();
+        dictionary.put_opt(&"bar".to_string(), &2, &bulk).unwrap();
+        dictionary.put(&"baz".to_string(), &3).unwrap();
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+        assert_eq!(dictionary.lookup_key(&"bar".to_string()).unwrap(), Some(2));
+
+        // The bulk write isn't logged, so reading the log stops at the gap it leaves.
+        assert_eq!(
+            dictionary
+                .updates_since(0)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![wal::WalUpdate {
+                sequence: 1,
+                operation: wal::WalOperation::Put {
+                    key: "foo".to_string(),
+                    value: 1
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn iter_keys() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        let mut expected = contents()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(
+            dictionary
+                .iter_keys()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        dictionary.merge(&"foo".to_string(), &10).unwrap();
+        dictionary.merge(&"foo".to_string(), &100).unwrap();
+        dictionary.merge(&"new".to_string(), &5).unwrap();
+
+        assert_eq!(
+            dictionary.lookup_key(&"foo".to_string()).unwrap(),
+            Some(111)
+        );
+        assert_eq!(dictionary.lookup_key(&"new".to_string()).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn compaction_filter() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            NonZeroCounts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        dictionary.database().db.flush().unwrap();
+        dictionary
+            .database()
+            .db
+            .compact_range(None::<&[u8]>, None::<&[u8]>);
+
+        assert_eq!(dictionary.lookup_key(&"qux".to_string()).unwrap(), None);
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn open_with_ttl() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_ttl(
+            directory.path(),
+            Duration::from_secs(1),
+            Dictionary::<mode::Writeable>::configure_options,
+        )
+        .unwrap();
+
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+
+        std::thread::sleep(Duration::from_secs(2));
+        dictionary
+            .database()
+            .db
+            .compact_range(None::<&[u8]>, None::<&[u8]>);
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), None);
+    }
+
+    #[test]
+    fn apply_retention() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        // Keys that are numbers are timestamps in seconds.
+        for key in ["100", "200", "4000000000", "foo"] {
+            dictionary.put(&key.to_string(), &1).unwrap();
+        }
+
+        let mut reports = vec![];
+        let result = dictionary
+            .apply_retention(
+                &retention::Retention::older_than(Duration::from_secs(86400)),
+                |key| {
+                    key.parse::<u64>()
+                        .ok()
+                        .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+                },
+                |progress| reports.push(*progress),
+            )
+            .unwrap();
+
+        let expected = retention::RetentionProgress {
+            scanned: 4,
+            removed: 2,
+        };
+
+        assert_eq!(result, expected);
+        assert_eq!(reports, vec![expected]);
+        assert_eq!(
+            dictionary
+                .iter()
+                .map(|result| result.map(|(key, _)| key))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec!["4000000000".to_string(), "foo".to_string()]
+        );
+    }
+
+    #[test]
+    fn bulk_load() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_for_bulk_load(
+            directory.path(),
+            Dictionary::<mode::Writeable>::configure_options,
+        )
+        .unwrap();
+
+        for (key, value) in contents() {
+            dictionary
+                .put_opt(&key.to_string(), &value, &config::WriteConfig::bulk())
+                .unwrap();
+        }
+
+        dictionary.finish_bulk_load().unwrap();
+
+        assert_eq!(
+            dictionary
+                .database()
+                .db
+                .property_int_value("rocksdb.num-files-at-level0")
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn ingest() {
+        let directory = tempfile::tempdir().unwrap();
+        let sst_directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        let mut contents = contents();
+        contents.sort();
+
+        let path = sst_directory.path().join("contents.sst");
+        let mut writer = dictionary.sst_writer(&path).unwrap();
+
+        for (key, value) in &contents {
+            writer.put(key, value).unwrap();
+        }
+
+        assert!(writer.put(&"a".to_string(), &0).is_err());
+        writer.finish().unwrap();
+
+        dictionary.ingest(vec![path]).unwrap();
+
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            contents
+        );
+    }
+
+    #[test]
+    fn ingest_sorted_comparator() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            ReversedDictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        let mut contents = contents();
+        contents.sort();
+
+        assert!(matches!(
+            dictionary.ingest_sorted(contents.clone()),
+            Err(Error::RocksDbTable(error::Error::OutOfOrderKey(_)))
+        ));
+
+        contents.reverse();
+        dictionary.ingest_sorted(contents.clone()).unwrap();
+
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            contents
+        );
+    }
+
+    #[test]
+    fn ingest_sorted() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        let mut contents = contents();
+        contents.sort();
+
+        let mut out_of_order = contents.clone();
+        out_of_order.swap(1, 2);
+
+        assert!(matches!(
+            dictionary.ingest_sorted(out_of_order),
+            Err(Error::RocksDbTable(error::Error::OutOfOrderKey(key))) if key == b"bar"
+        ));
+        assert_eq!(dictionary.iter().count(), 0);
+
+        dictionary.ingest_sorted(contents.clone()).unwrap();
+        dictionary.ingest_sorted(vec![]).unwrap();
+
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            contents
+        );
+
+        let more = (0..100)
+            .map(|i| (format!("key-{i:03}"), i))
+            .collect::<Vec<_>>();
+
+        std::thread::scope(|scope| {
+            for chunk in more.chunks(25) {
+                scope.spawn(|| dictionary.ingest_sorted(chunk.to_vec()).unwrap());
+            }
+        });
+
+        assert_eq!(dictionary.iter().count(), contents.len() + more.len());
+        assert!(std::fs::read_dir(directory.path())
+            .unwrap()
+            .all(|entry| !entry.unwrap().path().is_dir()));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn check_table() {
+        testing::check_table::<mode::ReadOnly, Dictionary<_>>(&contents());
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn table_round_trip() {
+        let mut entries = contents();
+        entries.push(("foo".to_string(), 2));
+
+        testing::table_round_trip::<Dictionary<_>>(&entries);
+        testing::index_round_trip::<Dictionary<_>, _>(&entries, |key| key[0..1].to_string());
+    }
+
+    #[test]
+    fn open_temporary() {
+        let dictionary =
+            Counts::<mode::Writeable>::open_temporary(Counts::<mode::Writeable>::configure_options)
+                .unwrap();
+        let path = dictionary.database().db.path().to_path_buf();
+
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+        dictionary.merge(&"foo".to_string(), &2).unwrap();
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(3));
+        assert!(path.exists());
+
+        drop(dictionary);
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn typed_comparator() {
+        let comparator =
+            comparator::Comparator::typed::<mode::Writeable, Dictionary<_>, _>("length", |a, b| {
+                a.len().cmp(&b.len())
+            });
+
+        assert_eq!(comparator.compare(b"zz", b"aaa"), std::cmp::Ordering::Less);
+        assert_eq!(
+            comparator.compare(b"ab", b"aa"),
+            std::cmp::Ordering::Greater
+        );
+        assert_eq!(
+            comparator.compare(&[255], b"aaa"),
+            std::cmp::Ordering::Greater
+        );
+    }
+
+    /// A dictionary with self-delimiting keys, as required for chunked values.
+    struct DelimitedDictionary<M> {
+        database: Database<M>,
+    }
+
+    impl<M: mode::Mode> Table<M> for DelimitedDictionary<M> {
+        type Counts = ();
+        type Error = Error;
+        type Key = String;
+        type KeyBytes = Vec<u8>;
+        type Value = u64;
+        type ValueBytes = [u8; 8];
+        type Index = String;
+        type IndexBytes = Vec<u8>;
+
+        fn database(&self) -> &Database<M> {
+            &self.database
+        }
+
+        fn from_database(database: Database<M>) -> Self {
+            Self { database }
+        }
+
+        fn get_counts(&self) -> Result<Self::Counts, Self::Error> {
+            Ok(())
+        }
+
+        fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
+            Ok(codec::KeyCodec::encode(key))
+        }
+
+        fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+            Dictionary::<M>::value_to_bytes(value)
+        }
+
+        fn index_to_bytes(index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
+            Ok(codec::KeyCodec::encode(index))
+        }
+
+        fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
+            Ok(codec::KeyCodec::decode(&bytes)?)
+        }
+
+        fn bytes_to_value(bytes: Cow<[u8]>) -> Result<Self::Value, Self::Error> {
+            Dictionary::<M>::bytes_to_value(bytes)
+        }
+    }
+
+    #[test]
+    fn chunked() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = chunked::ChunkedTable::new(
+            DelimitedDictionary::<mode::Writeable>::open_with_defaults(directory).unwrap(),
+            3,
+        );
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &u64::MAX).unwrap();
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        // A key that is a prefix of another key.
+        dictionary.put(&"fo".to_string(), &7).unwrap();
+        dictionary.delete(&"baz".to_string()).unwrap();
+
+        let mut expected = contents();
+        expected.retain(|(key, _)| key != "baz");
+        expected.push(("fo".to_string(), 7));
+        expected.sort();
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+        assert_eq!(dictionary.lookup_key(&"fo".to_string()).unwrap(), Some(7));
+        assert_eq!(dictionary.lookup_key(&"baz".to_string()).unwrap(), None);
+        assert_eq!(dictionary.lookup_key(&"f".to_string()).unwrap(), None);
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            expected
+        );
+        // Each eight-byte value is stored in three chunks.
+        assert_eq!(dictionary.table().exact_len().unwrap(), 15);
+    }
+
+    /// A toy cipher for testing, with a one-byte checksum in place of an authentication tag.
+    struct XorCipher(u8);
+
+    impl XorCipher {
+        fn checksum(&self, plaintext: &[u8], associated_data: &[u8]) -> u8 {
+            plaintext
+                .iter()
+                .chain(associated_data)
+                .fold(self.0, |sum, byte| sum.wrapping_mul(31).wrapping_add(*byte))
+        }
+    }
+
+    impl encrypted::Cipher for XorCipher {
+        fn encrypt(&self, plaintext: &[u8], associated_data: &[u8]) -> Vec<u8> {
+            let mut ciphertext = plaintext
+                .iter()
+                .map(|byte| byte ^ self.0)
+                .collect::<Vec<_>>();
+            ciphertext.push(self.checksum(plaintext, associated_data));
+            ciphertext
+        }
+
+        fn decrypt(&self, ciphertext: &[u8], associated_data: &[u8]) -> Option<Vec<u8>> {
+            let (checksum, ciphertext) = ciphertext.split_last()?;
+            let plaintext = ciphertext
+                .iter()
+                .map(|byte| byte ^ self.0)
+                .collect::<Vec<_>>();
+
+            (*checksum == self.checksum(&plaintext, associated_data)).then_some(plaintext)
+        }
+    }
+
+    #[test]
+    fn encrypted() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+        let encrypted = encrypted::EncryptedTable::new(dictionary, XorCipher(42));
+
+        for (key, value) in contents() {
+            encrypted.put(&key, &value).unwrap();
+        }
+
+        let mut expected = contents();
+        expected.sort();
+
+        assert_eq!(
+            encrypted.lookup_key(&"baz".to_string()).unwrap(),
+            Some(98765)
+        );
+        assert_eq!(encrypted.lookup_key(&"xyz".to_string()).unwrap(), None);
+        assert_eq!(
+            encrypted.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            expected
+        );
+
+        // A value moved to another key fails authentication.
+        let db = &encrypted.table().database().db;
+        db.put(b"abc", db.get(b"bar").unwrap().unwrap()).unwrap();
+
+        assert!(encrypted.lookup_key(&"abc".to_string()).is_err());
+        assert!(encrypted.table().lookup_key(&"bar".to_string()).is_err());
+    }
+
+    /// A (non-cryptographic) hasher for testing that produces hex strings.
+    struct HexHasher;
+
+    impl content::ContentHasher for HexHasher {
+        type Digest = String;
+
+        fn hash(bytes: &[u8]) -> String {
+            use std::hash::{Hash, Hasher};
+
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            bytes.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        }
+    }
+
+    #[test]
+    fn content_addressed() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = content::ContentAddressed::<_, _, HexHasher>::new(
+            Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap(),
+        );
+
+        let key = store.insert(&100).unwrap();
+
+        assert_eq!(store.insert(&100).unwrap(), key);
+        assert_ne!(store.insert(&200).unwrap(), key);
+        assert_eq!(store.lookup(&key).unwrap(), Some(100));
+        assert!(store.contains(&key).unwrap());
+        assert!(!store.contains(&"0".to_string()).unwrap());
+        assert_eq!(store.table().exact_len().unwrap(), 2);
+    }
+
+    #[test]
+    fn get_counts() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(dictionary.get_counts().unwrap(), contents().len());
+    }
+
+    #[test]
+    fn exact_len() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(dictionary.exact_len().unwrap(), contents().len() as u64);
+    }
+
+    #[test]
+    fn delete() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        dictionary.delete(&"foo".to_string()).unwrap();
+        dictionary.delete(&"XYZ".to_string()).unwrap();
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), None);
+        assert_eq!(dictionary.exact_len().unwrap(), 4);
+    }
+
+    #[cfg(feature = "lru")]
+    #[test]
+    fn cached() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = cached::CachedTable::new(
+            Counts::<mode::Writeable>::open_with_defaults(directory).unwrap(),
+            std::num::NonZeroUsize::new(2).unwrap(),
+        );
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let foo = "foo".to_string();
+
+        assert_eq!(dictionary.lookup_key(&foo).unwrap(), Some(1));
+        assert_eq!(
+            dictionary.lookup_key(&"bar".to_string()).unwrap(),
+            Some(1000)
+        );
+        assert_eq!(
+            dictionary.lookup_key(&"baz".to_string()).unwrap(),
+            Some(98765)
+        );
+        assert_eq!(dictionary.lookup_key(&"XYZ".to_string()).unwrap(), None);
+        assert_eq!(dictionary.cached_len(), 2);
+
+        dictionary.lookup_key(&foo).unwrap();
+        dictionary.merge(&foo, &10).unwrap();
+        assert_eq!(dictionary.lookup_key(&foo).unwrap(), Some(11));
+
+        dictionary.put(&foo, &5).unwrap();
+        assert_eq!(dictionary.lookup_key(&foo).unwrap(), Some(5));
+
+        dictionary.delete(&foo).unwrap();
+        assert_eq!(dictionary.lookup_key(&foo).unwrap(), None);
+
+        // Writes through the underlying table aren't seen until the cache is cleared.
+        dictionary.lookup_key(&"bar".to_string()).unwrap();
+        dictionary.table().put(&"bar".to_string(), &1).unwrap();
+        assert_eq!(
+            dictionary.lookup_key(&"bar".to_string()).unwrap(),
+            Some(1000)
+        );
+
+        dictionary.clear_cache();
+        assert_eq!(dictionary.lookup_key(&"bar".to_string()).unwrap(), Some(1));
+    }
+
+    #[cfg(feature = "lru")]
+    #[test]
+    fn cached_write_behind() {
+        let directory = tempfile::tempdir().unwrap();
+        let write_behind = cached::WriteBehind {
+            max_pending_bytes: 1024,
+            max_delay: Duration::from_secs(3600),
+        };
+        let dictionary = cached::CachedTable::with_write_behind(
+            Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap(),
+            std::num::NonZeroUsize::new(16).unwrap(),
+            write_behind,
+        );
+
+        let foo = "foo".to_string();
+        let bar = "bar".to_string();
+
+        dictionary.put(&foo, &1).unwrap();
+        dictionary.put(&bar, &2).unwrap();
+        dictionary.delete(&bar).unwrap();
+
+        assert_eq!(dictionary.lookup_key(&foo).unwrap(), Some(1));
+        assert_eq!(dictionary.lookup_key(&bar).unwrap(), None);
+        assert_eq!(dictionary.table().lookup_key(&foo).unwrap(), None);
+        assert_eq!(dictionary.pending_len(), 2);
+
+        // Looking up a key with a buffered merge flushes the buffer.
+        dictionary.merge(&foo, &10).unwrap();
+        assert_eq!(dictionary.lookup_key(&foo).unwrap(), Some(11));
+        assert_eq!(dictionary.table().lookup_key(&foo).unwrap(), Some(11));
+        assert_eq!(dictionary.pending_len(), 0);
+
+        // The buffer is flushed when it reaches the size limit.
+        for i in 0..100 {
+            dictionary.put(&format!("key-{i:03}"), &i).unwrap();
+        }
+        assert!(dictionary.pending_len() < 100);
+        assert!(dictionary.table().exact_len().unwrap() > 1);
+
+        dictionary.put(&bar, &3).unwrap();
+        dictionary.flush().unwrap();
+        assert_eq!(dictionary.table().lookup_key(&bar).unwrap(), Some(3));
+
+        dictionary.put(&bar, &4).unwrap();
+        drop(dictionary);
+
+        let dictionary = Counts::<mode::Writeable>::open_with_defaults(directory).unwrap();
+        assert_eq!(dictionary.lookup_key(&bar).unwrap(), Some(4));
+        assert_eq!(dictionary.exact_len().unwrap(), 102);
+    }
+
+    #[test]
+    fn multi_lookup() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let keys = ["foo", "XYZ", "bar", "foo"].map(|key| key.to_string());
+
+        assert_eq!(
+            dictionary.multi_lookup(&keys).unwrap(),
+            vec![Some(1), None, Some(1000), Some(1)]
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn asynchronous() {
+        use futures_core::Stream;
+        use std::pin::Pin;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = asynchronous::AsyncTable::new(
+            Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap(),
+        );
+
+        runtime.block_on(async {
+            for (key, value) in contents() {
+                dictionary.put(key, value).await.unwrap();
+            }
+
+            assert_eq!(
+                dictionary.lookup_key("foo".to_string()).await.unwrap(),
+                Some(1)
+            );
+            assert_eq!(
+                dictionary
+                    .multi_lookup(vec!["XYZ".to_string(), "bar".to_string()])
+                    .await
+                    .unwrap(),
+                vec![None, Some(1000)]
+            );
+
+            let mut stream = dictionary.iter();
+            let mut entries = vec![];
+
+            while let Some(result) =
+                std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+            {
+                entries.push(result.unwrap());
+            }
+
+            let mut expected = contents();
+            expected.sort();
+
+            assert_eq!(entries, expected);
+        });
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_iter() {
+        use rayon::iter::ParallelIterator;
+
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        let mut expected = (0..3000)
+            .map(|i| (format!("key-{:04}", i * 7 % 3000), i + 1))
+            .collect::<Vec<_>>();
+
+        for batch in expected.chunks(1000) {
+            for (key, value) in batch {
+                dictionary.put(key, value).unwrap();
+            }
+            dictionary.database().db.flush().unwrap();
+        }
+
+        expected.sort();
+
+        for partitions in [0, 1, 3, 16] {
+            let entries = parallel::par_iter(&dictionary, partitions)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(entries, expected);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn multi_lookup_parallel() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for i in 0..100 {
+            dictionary.put(&format!("key-{i:03}"), &i).unwrap();
+        }
+
+        let keys = (0..250)
+            .rev()
+            .map(|i| format!("key-{i:03}"))
+            .collect::<Vec<_>>();
+        let expected = (0..250)
+            .rev()
+            .map(|i| (i < 100).then_some(i))
+            .collect::<Vec<_>>();
+
+        for max_threads in [0, 1, 7, 1000] {
+            assert_eq!(
+                parallel::multi_lookup_parallel(&dictionary, &keys, max_threads).unwrap(),
+                expected
+            );
+        }
+
+        assert!(parallel::multi_lookup_parallel(&dictionary, &[], 4)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn multi_lookup_sorted() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let keys = ["qux", "XYZ", "bar", "foo", "abc", "bar"].map(|key| key.to_string());
+
+        for chunk_size in [1, 4, 100] {
+            assert_eq!(
+                dictionary.multi_lookup_sorted(&keys, chunk_size).unwrap(),
+                vec![Some(0), None, Some(1000), Some(1), Some(23), Some(1000)]
+            );
+        }
+
+        assert!(matches!(
+            dictionary.multi_lookup_sorted(&keys, 0),
+            Err(Error::RocksDbTable(error::Error::InvalidConfiguration(_)))
+        ));
+    }
+
+    #[test]
+    fn lookup_pinned() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let value_bytes = dictionary
+            .lookup_pinned(&"bar".to_string())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(&*value_bytes, 1000u64.to_be_bytes());
+        assert_eq!(
+            Dictionary::<mode::Writeable>::bytes_to_value(Cow::from(&*value_bytes)).unwrap(),
+            1000
+        );
+        assert!(dictionary
+            .lookup_pinned(&"XYZ".to_string())
+            .unwrap()
+            .is_none());
+    }
+
+    impl<M: mode::Mode> borrowed::TableRef<M> for Dictionary<M> {
+        type KeyRef<'a> = &'a str;
+        type ValueRef<'a> = u64;
+
+        fn bytes_to_key_ref(bytes: &[u8]) -> Result<Self::KeyRef<'_>, Self::Error> {
+            Ok(std::str::from_utf8(bytes)?)
+        }
+
+        fn bytes_to_value_ref(bytes: &[u8]) -> Result<Self::ValueRef<'_>, Self::Error> {
+            Self::bytes_to_value(Cow::from(bytes))
+        }
+    }
+
+    #[test]
+    fn iter_ref() {
+        use borrowed::TableRef;
+
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let mut entries = dictionary.iter_ref();
+        let mut keys = vec![];
+        let mut total = 0;
+
+        while let Some(result) = entries.next() {
+            let (key, value) = result.unwrap();
+            keys.push(key.len());
+            total += value;
+        }
+
+        assert!(entries.next().is_none());
+        assert_eq!(keys, vec![3; 5]);
+        assert_eq!(total, 1000 + 98765 + 1 + 23);
+    }
+
+    #[test]
+    fn iter_raw() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+        dictionary
+            .database()
+            .db
+            .put(b"invalid", b"not a u64")
+            .unwrap();
+
+        let entries = dictionary
+            .iter_raw()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(&*entries[0].0, b"foo");
+        assert_eq!(&*entries[1].1, b"not a u64");
+        assert!(dictionary.iter().any(|result| result.is_err()));
+    }
+
+    #[test]
+    fn parsed_statistics() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open(directory.path(), |mut options| {
+            options.enable_statistics();
+            options
+        })
+        .unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        dictionary.database().db.flush().unwrap();
+
+        for _ in 0..10 {
+            dictionary.lookup_key(&"foo".to_string()).unwrap();
+        }
+
+        let statistics = dictionary.parsed_statistics().unwrap();
+
+        assert!(statistics.bytes_written() > 0);
+        assert!(statistics.block_cache_hits() > 0);
+        assert!(statistics.block_cache_hit_rate().unwrap() > 0.5);
+        assert_eq!(statistics.get_micros().unwrap().count, 10);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open(directory.path(), |mut options| {
+            options.enable_statistics();
+            options
+        })
+        .unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let registry = prometheus::Registry::new();
+        let collector = metrics::MetricsCollector::new(&registry).unwrap();
+        collector.update("dictionary", &dictionary).unwrap();
+        collector.update("dictionary", &dictionary).unwrap();
+
+        let families = registry.gather();
+        let value = |name: &str| {
+            families
+                .iter()
+                .find(|family| family.name() == name)
+                .map(|family| family.get_metric().to_vec())
+                .unwrap()
+        };
+
+        let num_keys = value("rocksdb_estimate_num_keys");
+        assert_eq!(num_keys.len(), 1);
+        assert_eq!(num_keys[0].get_label()[0].value(), "dictionary");
+        assert_eq!(num_keys[0].get_gauge().get_value(), 5.0);
+
+        let bytes_written = value("rocksdb_ticker_total")
+            .into_iter()
+            .find(|metric| metric.get_label()[1].value() == "rocksdb.bytes.written")
+            .unwrap();
+        assert_eq!(
+            bytes_written.get_counter().get_value() as u64,
+            dictionary.parsed_statistics().unwrap().bytes_written()
+        );
+    }
+
+    #[test]
+    fn with_perf_context() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        dictionary.database().db.flush().unwrap();
+
+        let (value, report) = dictionary.with_perf_context(
+            rocksdb::perf::PerfStatsLevel::EnableTimeExceptForMutex,
+            |dictionary| dictionary.lookup_key(&"foo".to_string()).unwrap(),
+        );
+
+        assert_eq!(value, Some(1));
+        assert!(report.block_read_count + report.block_cache_hit_count > 0);
+        assert_eq!(report.write_wal_nanos, 0);
+
+        let ((), report) = dictionary.with_perf_context(
+            rocksdb::perf::PerfStatsLevel::EnableTimeExceptForMutex,
+            |dictionary| dictionary.put(&"abc".to_string(), &1).unwrap(),
+        );
+
+        assert_eq!(report.block_read_count, 0);
+        assert!(report.write_wal_nanos > 0);
+    }
+
+    #[test]
+    fn put_all() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        let entries = (0..2500)
+            .map(|i| (format!("key-{i:04}"), i))
+            .collect::<Vec<_>>();
+
+        dictionary
+            .put_all(entries.clone(), &stall::Backpressure::default())
+            .unwrap();
+
+        let state = dictionary.stall_state().unwrap();
+
+        assert!(!stall::Backpressure::default().should_wait(&state));
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            entries
+        );
+    }
+
+    #[test]
+    fn memory_budget() {
+        let budget = memory::MemoryBudget::new(64 * 1024 * 1024);
+        let directory_a = tempfile::tempdir().unwrap();
+        let directory_b = tempfile::tempdir().unwrap();
+
+        let dictionary_a =
+            Dictionary::<mode::Writeable>::open(directory_a.path(), budget.configure()).unwrap();
+        let dictionary_b =
+            Dictionary::<mode::Writeable>::open(directory_b.path(), budget.configure()).unwrap();
+
+        for i in 0..1000 {
+            dictionary_a.put(&format!("a-{i:04}"), &i).unwrap();
+            dictionary_b.put(&format!("b-{i:04}"), &i).unwrap();
+        }
+
+        let usage = budget.usage();
+
+        assert!(usage.write_buffers > 0);
+        assert!(usage.total >= usage.write_buffers);
+        assert!(usage.total <= budget.total_bytes());
+        assert_eq!(
+            dictionary_b.lookup_key(&"b-0999".to_string()).unwrap(),
+            Some(999)
+        );
+    }
+
+    #[test]
+    fn block_config() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            FilteredDictionary::<mode::Writeable>::open(directory.path(), |mut options| {
+                options.enable_statistics();
+                FilteredDictionary::<mode::Writeable>::configure_options(options)
+            })
+            .unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        dictionary.database().db.flush().unwrap();
+
+        for i in 0..100 {
+            assert_eq!(
+                dictionary.lookup_key(&format!("missing-{i}")).unwrap(),
+                None
+            );
+        }
+
+        let statistics = dictionary.parsed_statistics().unwrap();
+
+        // Most lookups for missing keys should be answered by the Bloom filter.
+        assert!(statistics.ticker("rocksdb.bloom.filter.useful") > 90);
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn open_with() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary = Counts::<mode::Writeable>::open_with(directory, |options| {
+            options.enable_statistics();
+        })
+        .unwrap();
+
+        let key = "foo".to_string();
+        dictionary.increment(&key, &2).unwrap();
+        dictionary.increment(&key, &3).unwrap();
+
+        // The table's merge operator is still configured.
+        assert_eq!(dictionary.lookup_key(&key).unwrap(), Some(5));
+        assert!(dictionary.statistics().is_some());
+    }
+
+    #[test]
+    fn builder() {
+        let directory = tempfile::tempdir().unwrap();
+        let wal_directory = tempfile::tempdir().unwrap();
+
+        let dictionary = Counts::<mode::Writeable>::builder()
+            .parallelism(2)
+            .write_buffer_size(1024 * 1024)
+            .wal_dir(wal_directory.path())
+            .block_config(&block::BlockConfig::default())
+            .configure(|options| options.enable_statistics())
+            .open(directory.path())
+            .unwrap();
+
+        dictionary.increment(&"foo".to_string(), &1).unwrap();
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+        assert!(dictionary.statistics().is_some());
+        assert!(std::fs::read_dir(wal_directory.path())
+            .unwrap()
+            .any(|entry| entry.unwrap().path().extension() == Some("log".as_ref())));
+
+        assert!(matches!(
+            Counts::<mode::Writeable>::builder()
+                .parallelism(0)
+                .open(directory.path()),
+            Err(error::Error::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            Counts::<mode::ReadOnly>::builder()
+                .ttl(Duration::from_secs(60))
+                .open(directory.path()),
+            Err(error::Error::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn builder_default_ttl() {
+        let directory = tempfile::tempdir().unwrap();
+        let wal_directory = tempfile::tempdir().unwrap();
+
+        {
+            // Read-only tables are opened with `error_if_log_file_exist`, which rejects any log file
+            // in the database directory (even an empty one), so the log is kept separately.
+            let dictionary = ExpiringDictionary::<mode::Writeable>::builder()
+                .wal_dir(wal_directory.path())
+                .open(directory.path())
+                .unwrap();
+            dictionary.put(&"foo".to_string(), &1).unwrap();
+            dictionary.database().db.flush().unwrap();
+        }
+
+        // The table's default time-to-live is ignored in read-only mode, as it is by `open`.
+        ExpiringDictionary::<mode::ReadOnly>::builder()
+            .open(directory.path())
+            .unwrap();
+        assert!(matches!(
+            ExpiringDictionary::<mode::ReadOnly>::builder()
+                .ttl(Duration::from_secs(60))
+                .open(directory.path()),
+            Err(error::Error::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn check_stored_options() {
+        let directory = tempfile::tempdir().unwrap();
+
+        Counts::<mode::Writeable>::check_stored_options(directory.path()).unwrap();
+        Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+        Counts::<mode::Writeable>::check_stored_options(directory.path()).unwrap();
+
+        // The raw table has no merge operator.
+        assert!(matches!(
+            raw::RawTable::<mode::Writeable>::check_stored_options(directory.path()),
+            Err(error::Error::OptionsMismatch {
+                option: "merge operator",
+                ..
+            })
+        ));
+
+        Counts::<mode::Writeable>::open_with(directory.path(), |options| {
+            prefix::PrefixExtractor::fixed(2).configure(options);
+        })
+        .unwrap();
+
+        assert!(matches!(
+            Counts::<mode::Writeable>::check_stored_options(directory.path()),
+            Err(error::Error::OptionsMismatch {
+                option: "prefix extractor",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn set_options() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        dictionary
+            .set_options(&[
+                ("write_buffer_size", "1048576"),
+                ("disable_auto_compactions", "true"),
+            ])
+            .unwrap();
+
+        let options_file = std::fs::read_dir(directory.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains("OPTIONS-"))
+            .max()
+            .unwrap();
+        let options = std::fs::read_to_string(options_file).unwrap();
+
+        assert!(options.contains("write_buffer_size=1048576"));
+        assert!(options.contains("disable_auto_compactions=true"));
+        assert!(dictionary.set_options(&[("not_an_option", "1")]).is_err());
+    }
+
+    #[test]
+    fn other_column_families() {
+        let directory = tempfile::tempdir().unwrap();
+
+        {
+            let mut options = Options::default();
+            options.create_if_missing(true);
+            options.create_missing_column_families(true);
+
+            let db = DB::open_cf(&options, directory.path(), ["other"]).unwrap();
+            db.put_cf(db.cf_handle("other").unwrap(), b"abc", b"def")
+                .unwrap();
+        }
+
+        assert_eq!(
+            Dictionary::<mode::Writeable>::list_column_families(directory.path()).unwrap(),
+            vec!["default", "other"]
+        );
+
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+
+        let db = &dictionary.database().db;
+
+        assert_eq!(
+            db.get_cf(db.cf_handle("other").unwrap(), b"abc").unwrap(),
+            Some(b"def".to_vec())
+        );
+        assert_eq!(dictionary.exact_len().unwrap(), 1);
+    }
+
+    #[test]
+    fn truncate() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        assert!(dictionary.truncate().unwrap() > 0);
+        assert_eq!(dictionary.exact_len().unwrap(), 0);
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), None);
+        assert_eq!(dictionary.truncate().unwrap(), 0);
+
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn copy_into() {
+        let source_directory = tempfile::tempdir().unwrap();
+        let source =
+            Dictionary::<mode::Writeable>::open_with_defaults(source_directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            source.put(&key, &value).unwrap();
+        }
+
+        let target_directory = tempfile::tempdir().unwrap();
+        let target =
+            Dictionary::<mode::Writeable>::open_with_defaults(target_directory.path()).unwrap();
+
+        let count = source
+            .copy_into(&target, |key, value| {
+                (value > 0).then(|| (key.to_uppercase(), value * 2))
+            })
+            .unwrap();
+
+        assert_eq!(count, 4);
+        assert_eq!(
+            target.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![
+                ("ABC".to_string(), 46),
+                ("BAR".to_string(), 2000),
+                ("BAZ".to_string(), 197530),
+                ("FOO".to_string(), 2),
+            ]
+        );
+
+        let count = source
+            .move_into(&target, |key, value| Some((key, value)))
+            .unwrap();
+
+        assert_eq!(count, 5);
+        assert_eq!(source.exact_len().unwrap(), 0);
+        assert_eq!(target.exact_len().unwrap(), 9);
+    }
+
+    #[test]
+    fn export_into() {
+        let source_directory = tempfile::tempdir().unwrap();
+        let source =
+            Dictionary::<mode::Writeable>::open_with_defaults(source_directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            source.put(&key, &value).unwrap();
         }
+
+        let target_directory = tempfile::tempdir().unwrap();
+        let target =
+            Dictionary::<mode::Writeable>::open_with_defaults(target_directory.path()).unwrap();
+        target.put(&"foo".to_string(), &1000).unwrap();
+        target.put(&"xyz".to_string(), &1).unwrap();
+
+        assert_eq!(source.export_into(&target).unwrap(), 5);
+        assert_eq!(target.exact_len().unwrap(), 6);
+        assert_eq!(target.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+        assert_eq!(target.lookup_key(&"xyz".to_string()).unwrap(), Some(1));
+        assert!(std::fs::read_dir(target_directory.path())
+            .unwrap()
+            .all(|entry| !entry.unwrap().path().is_dir()));
     }
-}
 
-/// Allows selection of values to decode (if for example this is expensive).
-pub enum SelectedValueIndexIterator<'a, M, T: Table<M>, P> {
-    ValidIndex {
-        underlying: DBIterator<'a>,
-        index_bytes: T::IndexBytes,
-        pred: P,
-        _mode: PhantomData<M>,
-        _table: PhantomData<T>,
-    },
-    InvalidIndex {
-        error: Option<T::Error>,
-    },
-}
+    #[test]
+    fn repair_and_destroy() {
+        let directory = tempfile::tempdir().unwrap();
 
-impl<'a, M: mode::Mode, T: Table<M>, P: Fn(&T::Key) -> bool> Iterator
-    for SelectedValueIndexIterator<'a, M, T, P>
-{
-    type Item = Result<(T::Key, Option<T::Value>), T::Error>;
+        {
+            let dictionary =
+                Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            SelectedValueIndexIterator::ValidIndex {
-                underlying,
-                index_bytes,
-                pred,
-                ..
-            } => underlying.next().and_then(|result| match result {
-                Ok((key_bytes, value_bytes)) => {
-                    if key_bytes.starts_with(index_bytes.as_ref()) {
-                        Some(
-                            T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
-                                if (pred)(&key) {
-                                    T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
-                                        .map(|value| (key, Some(value)))
-                                } else {
-                                    Ok((key, None))
-                                }
-                            }),
-                        )
-                    } else {
-                        None
-                    }
-                }
-                Err(error) => Some(Err(T::Error::from(error.into()))),
-            }),
-            SelectedValueIndexIterator::InvalidIndex { error } => error.take().map(Err),
+            for (key, value) in contents() {
+                dictionary.put(&key, &value).unwrap();
+            }
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        Dictionary::<mode::Writeable>::repair(directory.path()).unwrap();
 
-    #[derive(thiserror::Error, Debug)]
-    pub enum Error {
-        #[error("RocksDb table error")]
-        RocksDbTable(#[from] error::Error),
-        #[error("String encoding error")]
-        Utf8(#[from] std::str::Utf8Error),
-    }
+        {
+            let dictionary =
+                Dictionary::<mode::ReadOnly>::open_with_defaults(directory.path()).unwrap();
+            assert_eq!(dictionary.exact_len().unwrap(), 5);
+        }
 
-    struct Dictionary<M> {
-        database: Database<M>,
+        Dictionary::<mode::Writeable>::destroy(directory.path()).unwrap();
+
+        assert!(!directory.path().join("CURRENT").exists());
     }
 
-    impl<M: mode::Mode> Table<M> for Dictionary<M> {
-        type Counts = usize;
-        type Error = Error;
-        type Key = String;
-        type KeyBytes = Vec<u8>;
-        type Value = u64;
-        type ValueBytes = [u8; 8];
-        type Index = String;
-        type IndexBytes = Vec<u8>;
+    #[test]
+    fn verify() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
 
-        fn database(&self) -> &Database<M> {
-            &self.database
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
         }
 
-        fn from_database(database: Database<M>) -> Self {
-            Self { database }
-        }
+        let report = dictionary.verify().unwrap();
 
-        fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
-            Ok(key.as_bytes().to_vec())
+        assert!(report.is_ok());
+        assert_eq!(report.scanned, 5);
+
+        dictionary.database().db.put(b"\xff", b"").unwrap();
+
+        let report = dictionary.verify().unwrap();
+
+        assert!(!report.is_ok());
+        assert_eq!(report.scanned, 6);
+        assert_eq!(report.undecodable.len(), 1);
+        assert_eq!(report.undecodable[0].key_bytes, b"\xff");
+        assert!(matches!(report.undecodable[0].error, Error::Utf8(_)));
+    }
+
+    #[test]
+    fn schema_mismatch() {
+        let directory = tempfile::tempdir().unwrap();
+
+        {
+            let dictionary =
+                FingerprintedCounts::<mode::Writeable>::open_with_defaults(directory.path())
+                    .unwrap();
+            dictionary.put(&"foo".to_string(), &1).unwrap();
         }
 
-        fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
-            Ok(value.to_be_bytes())
+        assert!(
+            FingerprintedCounts::<mode::Writeable>::list_column_families(directory.path())
+                .unwrap()
+                .contains(&schema::META_COLUMN_FAMILY_NAME.to_string())
+        );
+
+        let result =
+            FingerprintedDictionary::<mode::Writeable>::open_with_defaults(directory.path());
+
+        match result {
+            Err(error::Error::SchemaMismatch { stored, expected }) => {
+                assert_eq!(
+                    *stored,
+                    schema::Fingerprint::of::<mode::Writeable, FingerprintedCounts<mode::Writeable>>(
+                    )
+                );
+                assert_eq!(expected.merge_operator, None);
+            }
+            _ => panic!("Expected schema mismatch"),
         }
 
-        fn index_to_bytes(index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
-            Ok(index.as_bytes().to_vec())
+        // Tables that don't store fingerprints don't check them.
+        assert!(raw::RawTable::<mode::Writeable>::open_with_defaults(directory.path()).is_ok());
+
+        let dictionary =
+            FingerprintedCounts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn schema_fingerprint_opt_in() {
+        let directory = tempfile::tempdir().unwrap();
+
+        {
+            let dictionary =
+                Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+            dictionary.put(&"foo".to_string(), &1).unwrap();
         }
 
-        fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
-            Ok(std::str::from_utf8(bytes.as_ref())?.to_string())
+        assert!(raw::RawTable::<mode::Writeable>::open_with_defaults(directory.path()).is_ok());
+        assert_eq!(
+            Counts::<mode::Writeable>::list_column_families(directory.path()).unwrap(),
+            vec!["default".to_string()]
+        );
+
+        let dictionary = Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+        assert!(dictionary.migrate(&[]).is_err());
+    }
+
+    struct AddEntry(u32, &'static str);
+
+    impl migration::Migration<mode::Writeable, FingerprintedDictionary<mode::Writeable>> for AddEntry {
+        fn version(&self) -> u32 {
+            self.0
         }
 
-        fn bytes_to_value(bytes: Cow<[u8]>) -> Result<Self::Value, Self::Error> {
-            Ok(u64::from_be_bytes(
-                bytes.as_ref()[0..8]
-                    .try_into()
-                    .map_err(|_| error::Error::InvalidValue(bytes.as_ref().to_vec()))?,
-            ))
+        fn migrate(
+            &self,
+            table: &FingerprintedDictionary<mode::Writeable>,
+            batch: &mut migration::MigrationBatch<
+                mode::Writeable,
+                FingerprintedDictionary<mode::Writeable>,
+            >,
+        ) -> Result<(), Error> {
+            let count = table.exact_len()?;
+            batch.put(&self.1.to_string(), &count)
         }
+    }
 
-        fn get_counts(&self) -> Result<Self::Counts, Error> {
-            let mut count = 0;
+    #[test]
+    fn migrate() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            FingerprintedDictionary::<mode::Writeable>::open_with_defaults(directory.path())
+                .unwrap();
 
-            for result in self.iter() {
-                result?;
-                count += 1;
-            }
+        assert_eq!(dictionary.migration_version().unwrap(), None);
 
-            Ok(count)
-        }
+        let first = AddEntry(1, "first");
+        let second = AddEntry(2, "second");
+        let third = AddEntry(3, "third");
+
+        assert_eq!(dictionary.migrate(&[&second, &first]).unwrap(), vec![1, 2]);
+        assert_eq!(dictionary.migration_version().unwrap(), Some(2));
+        assert_eq!(
+            dictionary.migrate(&[&first, &second, &third]).unwrap(),
+            vec![3]
+        );
+        assert_eq!(dictionary.migration_version().unwrap(), Some(3));
+
+        assert_eq!(
+            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![
+                ("first".to_string(), 0),
+                ("second".to_string(), 1),
+                ("third".to_string(), 2),
+            ]
+        );
+
+        assert!(dictionary
+            .migrate(&[&third, &AddEntry(3, "other")])
+            .is_err());
     }
 
-    fn contents() -> Vec<(String, u64)> {
-        vec![
-            ("bar", 1000),
-            ("baz", 98765),
-            ("foo", 1),
-            ("abc", 23),
-            ("qux", 0),
-        ]
-        .into_iter()
-        .map(|(key, value)| (key.to_string(), value))
-        .collect()
+    #[test]
+    fn audited() {
+        let directory = tempfile::tempdir().unwrap();
+
+        {
+            let dictionary = audit::AuditedTable::new(
+                AuditedCounts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap(),
+            )
+            .unwrap();
+
+            dictionary.put(&"foo".to_string(), &1).unwrap();
+            dictionary.merge(&"foo".to_string(), &2).unwrap();
+            dictionary.delete(&"foo".to_string()).unwrap();
+
+            let records = dictionary.records().collect::<Result<Vec<_>, _>>().unwrap();
+
+            assert_eq!(
+                records
+                    .iter()
+                    .map(|record| (
+                        record.sequence,
+                        record.operation,
+                        record.key.as_str(),
+                        record.value,
+                        record.old_value
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (0, audit::Operation::Put, "foo", Some(1), None),
+                    (1, audit::Operation::Merge, "foo", Some(2), Some(1)),
+                    (2, audit::Operation::Delete, "foo", None, Some(3)),
+                ]
+            );
+            assert_eq!(
+                dictionary.table().lookup_key(&"foo".to_string()).unwrap(),
+                None
+            );
+        }
+
+        let dictionary = audit::AuditedTable::new(
+            AuditedCounts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap(),
+        )
+        .unwrap();
+
+        dictionary.put(&"bar".to_string(), &10).unwrap();
+
+        let records = dictionary
+            .records_from(2)
+            .map(|record| record.map(|record| (record.sequence, record.operation)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            records,
+            vec![(2, audit::Operation::Delete), (3, audit::Operation::Put)]
+        );
+
+        assert!(audit::AuditedTable::new(
+            raw::RawTable::<mode::Writeable>::open_temporary(|options| options).unwrap()
+        )
+        .is_err());
     }
 
     #[test]
-    fn lookup_key() {
+    fn soft_delete() {
         let directory = tempfile::tempdir().unwrap();
-        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+        let dictionary = soft_delete::SoftDeleteTable::new(
+            SoftDeleteDictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap(),
+        );
 
         for (key, value) in contents() {
-            dictionary.put(&key.to_string(), &value).unwrap();
+            dictionary.put(&key, &value).unwrap();
         }
 
-        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), Some(1));
+        dictionary.delete(&"foo".to_string()).unwrap();
+
+        assert_eq!(dictionary.lookup_key(&"foo".to_string()).unwrap(), None);
+        assert!(dictionary.deleted_at(&"foo".to_string()).unwrap().is_some());
         assert_eq!(
             dictionary.lookup_key(&"bar".to_string()).unwrap(),
             Some(1000)
         );
-        assert_eq!(dictionary.lookup_key(&"XYZ".to_string()).unwrap(), None);
+        assert_eq!(
+            dictionary
+                .iter()
+                .map(|result| result.map(|(key, _)| key))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec!["abc", "bar", "baz", "qux"]
+        );
+        assert_eq!(
+            dictionary
+                .iter_deleted()
+                .map(|result| result.map(|(key, _)| key))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec!["foo"]
+        );
+
+        // The grace period is zero, so the tombstone is removed by the next compaction.
+        std::thread::sleep(Duration::from_millis(10));
+        let db = &dictionary.table().database().db;
+        db.flush().unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+
+        assert_eq!(dictionary.deleted_at(&"foo".to_string()).unwrap(), None);
+        assert_eq!(dictionary.iter_deleted().count(), 0);
+        assert_eq!(dictionary.lookup_key(&"qux".to_string()).unwrap(), Some(0));
     }
 
     #[test]
-    fn lookup_index() {
+    fn subscribe() {
         let directory = tempfile::tempdir().unwrap();
-        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+        let dictionary = subscription::SubscribableTable::new(
+            Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap(),
+        );
 
-        for (key, value) in contents() {
-            dictionary.put(&key.to_string(), &value).unwrap();
+        let all = dictionary.subscribe();
+        let b = dictionary.subscribe_index(&"b".to_string()).unwrap();
+
+        {
+            let _dropped = dictionary.subscribe();
         }
 
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+        dictionary.put(&"bar".to_string(), &2).unwrap();
+        dictionary.merge(&"bar".to_string(), &3).unwrap();
+        dictionary.delete(&"foo".to_string()).unwrap();
+
+        assert_eq!(dictionary.subscriber_count(), 2);
+        assert_eq!(all.try_iter().count(), 4);
         assert_eq!(
-            &dictionary
-                .lookup_index(&"ba".to_string())
-                .collect::<Result<Vec<_>, _>>()
-                .unwrap(),
-            &contents()[0..2].to_vec()
+            b.try_iter().collect::<Vec<_>>(),
+            vec![
+                subscription::ChangeEvent::Put {
+                    key: "bar".to_string(),
+                    value: 2
+                },
+                subscription::ChangeEvent::Merge {
+                    key: "bar".to_string(),
+                    operand: 3
+                },
+            ]
+        );
+        assert_eq!(
+            dictionary.table().lookup_key(&"bar".to_string()).unwrap(),
+            Some(5)
         );
     }
 
     #[test]
-    fn iter() {
+    fn updates_since() {
         let directory = tempfile::tempdir().unwrap();
-        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+        let dictionary = Counts::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        dictionary.put(&"foo".to_string(), &1).unwrap();
+        let sequence = dictionary.latest_sequence_number();
+        dictionary.put(&"bar".to_string(), &2).unwrap();
+        dictionary.merge(&"bar".to_string(), &3).unwrap();
+        dictionary.delete(&"foo".to_string()).unwrap();
+
+        let updates = dictionary
+            .updates_since(sequence)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            updates,
+            vec![
+                wal::WalUpdate {
+                    sequence: sequence + 1,
+                    operation: wal::WalOperation::Put {
+                        key: "bar".to_string(),
+                        value: 2
+                    }
+                },
+                wal::WalUpdate {
+                    sequence: sequence + 3,
+                    operation: wal::WalOperation::Delete {
+                        key: "foo".to_string()
+                    }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn export_since() {
+        let source_directory = tempfile::tempdir().unwrap();
+        let source =
+            Dictionary::<mode::Writeable>::open_with_defaults(source_directory.path()).unwrap();
+        let target_directory = tempfile::tempdir().unwrap();
+        let target =
+            Dictionary::<mode::Writeable>::open_with_defaults(target_directory.path()).unwrap();
 
         for (key, value) in contents() {
-            dictionary.put(&key.to_string(), &value).unwrap();
+            source.put(&key, &value).unwrap();
         }
 
-        let mut expected = contents();
-        expected.sort();
+        let mut exported = vec![];
+        let summary = source.export_since(0, &mut exported).unwrap();
+
+        assert_eq!(summary.puts, 5);
+        assert_eq!(target.import_changes(exported.as_slice()).unwrap(), summary);
+        assert_eq!(target.exact_len().unwrap(), 5);
+
+        source.put(&"foo".to_string(), &2).unwrap();
+        source.put(&"foo".to_string(), &3).unwrap();
+        source.delete(&"bar".to_string()).unwrap();
+
+        let mut exported = vec![];
+        let summary = source
+            .export_since(summary.sequence, &mut exported)
+            .unwrap();
+
+        assert_eq!((summary.puts, summary.deletes), (1, 1));
+
+        target.import_changes(exported.as_slice()).unwrap();
+
+        assert_eq!(target.lookup_key(&"foo".to_string()).unwrap(), Some(3));
+        assert_eq!(target.lookup_key(&"bar".to_string()).unwrap(), None);
+        assert!(target
+            .import_changes(&exported[..exported.len() - 1])
+            .is_err());
+        assert!(target.import_changes(&b"XXXX"[..]).is_err());
+
+        // A corrupt length fails without allocating the claimed length up front.
+        let mut corrupt = exported[..13].to_vec();
+        corrupt.extend_from_slice(&[0, 255, 255, 255, 255, 1, 2, 3]);
+        assert!(target.import_changes(corrupt.as_slice()).is_err());
+    }
+
+    #[test]
+    fn export_since_incomplete() {
+        let directory = tempfile::tempdir().unwrap();
+        let dictionary =
+            Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for (key, value) in contents() {
+            dictionary.put(&key, &value).unwrap();
+        }
+
+        let sequence = dictionary.latest_sequence_number();
+        dictionary.truncate().unwrap();
 
+        assert!(matches!(
+            dictionary.export_since(sequence, std::io::sink()),
+            Err(Error::RocksDbTable(error::Error::IncompleteLog(next))) if next == sequence + 1
+        ));
+
+        let sequence = dictionary.latest_sequence_number();
+        dictionary
+            .put_opt(&"foo".to_string(), &1, &config::WriteConfig::bulk())
+            .unwrap();
+        dictionary.put(&"bar".to_string(), &2).unwrap();
+
+        assert!(matches!(
+            dictionary.export_since(sequence, std::io::sink()),
+            Err(Error::RocksDbTable(error::Error::IncompleteLog(next))) if next == sequence + 1
+        ));
+    }
+
+    #[test]
+    fn follower() {
+        let directory = tempfile::tempdir().unwrap();
+        let secondary_directory = tempfile::tempdir().unwrap();
+        let primary = Dictionary::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+        let follower = replication::Follower::new(
+            Dictionary::<mode::Secondary>::open_as_secondary_with_defaults(
+                directory.path(),
+                secondary_directory.path(),
+            )
+            .unwrap(),
+        );
+
+        for (key, value) in contents() {
+            primary.put(&key, &value).unwrap();
+        }
+
+        let status = replication::PrimaryStatus::read(&primary).unwrap();
+
+        assert_eq!(status.latest_sequence, primary.latest_sequence_number());
+        assert!(!status.wal_segments.is_empty());
+
+        let progress = follower.catch_up(Some(status.latest_sequence)).unwrap();
+
+        assert_eq!(progress.lag, Some(0));
+        assert_eq!(progress.applied(), 5);
         assert_eq!(
-            dictionary.iter().collect::<Result<Vec<_>, _>>().unwrap(),
-            expected
+            follower.table().lookup_key(&"foo".to_string()).unwrap(),
+            Some(1)
+        );
+
+        primary.put(&"foo".to_string(), &2).unwrap();
+
+        let mut catch_ups = 0;
+        follower
+            .follow(
+                Duration::ZERO,
+                || Some(primary.latest_sequence_number()),
+                |progress| {
+                    catch_ups += 1;
+                    progress.lag != Some(0)
+                },
+            )
+            .unwrap();
+
+        assert_eq!(catch_ups, 1);
+        assert_eq!(
+            follower.table().lookup_key(&"foo".to_string()).unwrap(),
+            Some(2)
         );
     }
 
     #[test]
-    fn get_counts() {
-        let directory = tempfile::tempdir().unwrap();
-        let dictionary = Dictionary::<mode::Writeable>::open_with_defaults(directory).unwrap();
+    fn diff() {
+        let left_directory = tempfile::tempdir().unwrap();
+        let left =
+            Dictionary::<mode::Writeable>::open_with_defaults(left_directory.path()).unwrap();
+        let right_directory = tempfile::tempdir().unwrap();
+        let right =
+            Dictionary::<mode::Writeable>::open_with_defaults(right_directory.path()).unwrap();
 
         for (key, value) in contents() {
-            dictionary.put(&key.to_string(), &value).unwrap();
+            left.put(&key, &value).unwrap();
+            right.put(&key, &value).unwrap();
         }
 
-        assert_eq!(dictionary.get_counts().unwrap(), contents().len());
+        left.delete(&"abc".to_string()).unwrap();
+        right.put(&"bar".to_string(), &1001).unwrap();
+        right.delete(&"qux".to_string()).unwrap();
+        right.put(&"xyz".to_string(), &1).unwrap();
+
+        assert_eq!(
+            diff::diff(&left, &right)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![
+                diff::DiffItem::OnlyRight {
+                    key: "abc".to_string(),
+                    value: 23
+                },
+                diff::DiffItem::Changed {
+                    key: "bar".to_string(),
+                    left: 1000,
+                    right: 1001
+                },
+                diff::DiffItem::OnlyLeft {
+                    key: "qux".to_string(),
+                    value: 0
+                },
+                diff::DiffItem::OnlyRight {
+                    key: "xyz".to_string(),
+                    value: 1
+                },
+            ]
+        );
+
+        assert_eq!(diff::diff(&left, &left).count(), 0);
     }
 }
@@ -0,0 +1,81 @@
+//! Memory budgets shared across tables.
+
+use rocksdb::{BlockBasedOptions, Cache, Options, WriteBufferManager};
+
+/// The fraction of a budget that can be used by memtables.
+const WRITE_BUFFER_FRACTION: usize = 4;
+
+/// Current memory usage for a budget, in bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    /// Total usage (including memtables, which are charged to the block cache).
+    pub total: usize,
+    pub write_buffers: usize,
+}
+
+/// A block cache and write buffer limit shared by every table opened with it.
+///
+/// Memtables can use up to a quarter of the budget, and their memory is charged to the block
+/// cache, so that blocks and memtables together stay within the budget.
+#[derive(Clone)]
+pub struct MemoryBudget {
+    total_bytes: usize,
+    cache: Cache,
+    write_buffer_manager: WriteBufferManager,
+}
+
+impl MemoryBudget {
+    pub fn new(total_bytes: usize) -> Self {
+        let cache = Cache::new_lru_cache(total_bytes);
+        let write_buffer_manager = WriteBufferManager::new_write_buffer_manager_with_cache(
+            total_bytes / WRITE_BUFFER_FRACTION,
+            false,
+            cache.clone(),
+        );
+
+        Self {
+            total_bytes,
+            cache,
+            write_buffer_manager,
+        }
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            total: self.cache.get_usage(),
+            write_buffers: self.write_buffer_manager.get_usage(),
+        }
+    }
+
+    /// Use the shared block cache in the given block-based table options.
+    pub fn configure_block_options(&self, block_options: &mut BlockBasedOptions) {
+        block_options.set_block_cache(&self.cache);
+        block_options.set_cache_index_and_filter_blocks(true);
+    }
+
+    /// An options initialization function that applies this budget.
+    ///
+    /// This replaces any block-based table options, so tables that need other block options should
+    /// use [`MemoryBudget::configure_block_options`] when building them instead.
+    pub fn configure(&self) -> impl FnMut(Options) -> Options + Clone {
+        let budget = self.clone();
+
+        move |mut options| {
+            let mut block_options = BlockBasedOptions::default();
+            budget.configure_block_options(&mut block_options);
+
+            options.set_block_based_table_factory(&block_options);
+            options.set_write_buffer_manager(&budget.write_buffer_manager);
+
+            options
+        }
+    }
+}
@@ -0,0 +1,406 @@
+//! Merge operator configuration.
+
+use crate::Table;
+use rocksdb::{MergeOperands, Options};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// A byte-level merge function (see [`rocksdb::merge_operator::MergeFn`]).
+pub type MergeFn =
+    Arc<dyn Fn(&[u8], Option<&[u8]>, &MergeOperands) -> Option<Vec<u8>> + Send + Sync>;
+
+/// A named merge operator that can be installed on database options.
+#[derive(Clone)]
+pub enum MergeOperator {
+    /// A merge operator where partial merges of operands use the same function as full merges.
+    Associative { name: String, merge: MergeFn },
+    /// A merge operator with separate full and partial merge functions.
+    ///
+    /// The partial merge function combines operands without an existing value, and may return
+    /// `None` to indicate that the operands can't be combined until a full merge.
+    Full {
+        name: String,
+        full_merge: MergeFn,
+        partial_merge: MergeFn,
+    },
+}
+
+impl MergeOperator {
+    pub fn associative<F>(name: &str, merge: F) -> Self
+    where
+        F: Fn(&[u8], Option<&[u8]>, &MergeOperands) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        Self::Associative {
+            name: name.to_string(),
+            merge: Arc::new(merge),
+        }
+    }
+
+    pub fn full<F, PF>(name: &str, full_merge: F, partial_merge: PF) -> Self
+    where
+        F: Fn(&[u8], Option<&[u8]>, &MergeOperands) -> Option<Vec<u8>> + Send + Sync + 'static,
+        PF: Fn(&[u8], Option<&[u8]>, &MergeOperands) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        Self::Full {
+            name: name.to_string(),
+            full_merge: Arc::new(full_merge),
+            partial_merge: Arc::new(partial_merge),
+        }
+    }
+
+    /// A merge operator for a table that implements merging over decoded values.
+    ///
+    /// If any value can't be decoded or encoded, the merge fails (and RocksDB will report a
+    /// corruption error).
+    pub fn typed<M: 'static, T: TypedMerge<M> + 'static>(name: &str) -> Self {
+        Self::full(
+            name,
+            |_key, existing, operands| {
+                let existing = match existing {
+                    Some(bytes) => Some(T::bytes_to_value(Cow::from(bytes)).ok()?),
+                    None => None,
+                };
+
+                let operands = operands
+                    .iter()
+                    .map(|bytes| T::bytes_to_value(Cow::from(bytes)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()?;
+
+                let merged = T::merge_values(existing, operands.into_iter());
+
+                T::value_to_bytes(&merged)
+                    .ok()
+                    .map(|bytes| bytes.as_ref().to_vec())
+            },
+            // We can't assume that operands can be combined without the existing value.
+            |_key, _existing, _operands| None,
+        )
+    }
+
+    /// Addition of big-endian `u32` values (wrapping on overflow).
+    pub fn add_u32() -> Self {
+        Self::associative("add_u32", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                u32::from_be_bytes,
+                u32::to_be_bytes,
+                |a, b| a.wrapping_add(b),
+            )
+        })
+    }
+
+    /// Addition of big-endian `u64` values (wrapping on overflow).
+    pub fn add_u64() -> Self {
+        Self::associative("add_u64", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                u64::from_be_bytes,
+                u64::to_be_bytes,
+                |a, b| a.wrapping_add(b),
+            )
+        })
+    }
+
+    /// Addition of big-endian `i64` values (wrapping on overflow).
+    pub fn add_i64() -> Self {
+        Self::associative("add_i64", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                i64::from_be_bytes,
+                i64::to_be_bytes,
+                |a, b| a.wrapping_add(b),
+            )
+        })
+    }
+
+    /// Maximum of big-endian `u64` values.
+    pub fn max_u64() -> Self {
+        Self::associative("max_u64", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                u64::from_be_bytes,
+                u64::to_be_bytes,
+                u64::max,
+            )
+        })
+    }
+
+    /// Minimum of big-endian `u64` values.
+    pub fn min_u64() -> Self {
+        Self::associative("min_u64", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                u64::from_be_bytes,
+                u64::to_be_bytes,
+                u64::min,
+            )
+        })
+    }
+
+    /// Maximum of big-endian `i64` values.
+    pub fn max_i64() -> Self {
+        Self::associative("max_i64", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                i64::from_be_bytes,
+                i64::to_be_bytes,
+                i64::max,
+            )
+        })
+    }
+
+    /// Minimum of big-endian `i64` values.
+    pub fn min_i64() -> Self {
+        Self::associative("min_i64", |_key, existing, operands| {
+            fold_fixed(
+                existing,
+                operands,
+                i64::from_be_bytes,
+                i64::to_be_bytes,
+                i64::min,
+            )
+        })
+    }
+
+    /// Appending to length-prefixed lists (see [`encode_list`]), optionally keeping only the last
+    /// `max_len` items.
+    ///
+    /// Operands are encoded lists of items to append, so a single merge can append several items.
+    pub fn list_append(max_len: Option<usize>) -> Self {
+        let name = match max_len {
+            Some(max_len) => format!("list_append_last_{}", max_len),
+            None => "list_append".to_string(),
+        };
+
+        Self::associative(&name, move |_key, existing, operands| {
+            let mut items = vec![];
+
+            for bytes in existing.into_iter().chain(operands) {
+                items.extend(decode_list(bytes).ok()?);
+            }
+
+            let skip = max_len.map_or(0, |max_len| items.len().saturating_sub(max_len));
+
+            Some(encode_list(&items[skip..]))
+        })
+    }
+
+    /// Deduplicated union of sorted sets of fixed-size items (e.g. big-endian `u64` identifiers).
+    ///
+    /// Values and operands are concatenations of items, and merged values are sorted by item bytes.
+    /// The item size must be positive.
+    pub fn set_union(item_size: usize) -> Self {
+        assert!(item_size > 0, "Item size must be positive");
+
+        Self::associative(
+            &format!("set_union_{}", item_size),
+            move |_key, existing, operands| {
+                let mut items = vec![];
+
+                for bytes in existing.into_iter().chain(operands) {
+                    if bytes.len() % item_size != 0 {
+                        return None;
+                    }
+
+                    items.extend(bytes.chunks_exact(item_size));
+                }
+
+                items.sort_unstable();
+                items.dedup();
+
+                Some(items.concat())
+            },
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Associative { name, .. } => name,
+            Self::Full { name, .. } => name,
+        }
+    }
+
+    /// Install this merge operator on the given options.
+    pub fn configure(&self, options: &mut Options) {
+        match self {
+            Self::Associative { name, merge } => {
+                let merge = merge.clone();
+                options.set_merge_operator_associative(name, move |key, existing, operands| {
+                    merge(key, existing, operands)
+                });
+            }
+            Self::Full {
+                name,
+                full_merge,
+                partial_merge,
+            } => {
+                let full_merge = full_merge.clone();
+                let partial_merge = partial_merge.clone();
+                options.set_merge_operator(
+                    name,
+                    move |key, existing, operands| full_merge(key, existing, operands),
+                    move |key, existing, operands| partial_merge(key, existing, operands),
+                );
+            }
+        }
+    }
+}
+
+/// Encode a list of items as a sequence of big-endian `u32` lengths followed by item bytes.
+pub fn encode_list<I: AsRef<[u8]>>(items: &[I]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(
+        items
+            .iter()
+            .map(|item| item.as_ref().len() + 4)
+            .sum::<usize>(),
+    );
+
+    for item in items {
+        let item = item.as_ref();
+        bytes.extend_from_slice(&(item.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(item);
+    }
+
+    bytes
+}
+
+/// Decode a list of items encoded with [`encode_list`].
+pub fn decode_list(bytes: &[u8]) -> Result<Vec<&[u8]>, crate::error::Error> {
+    let mut items = vec![];
+    let mut remaining = bytes;
+
+    while !remaining.is_empty() {
+        let (len_bytes, rest) = remaining
+            .split_first_chunk::<4>()
+            .ok_or_else(|| crate::error::Error::InvalidValue(bytes.to_vec()))?;
+        let len = u32::from_be_bytes(*len_bytes) as usize;
+
+        if rest.len() < len {
+            return Err(crate::error::Error::InvalidValue(bytes.to_vec()));
+        }
+
+        let (item, rest) = rest.split_at(len);
+        items.push(item);
+        remaining = rest;
+    }
+
+    Ok(items)
+}
+
+/// Combine fixed-width values (failing if any value has the wrong width).
+fn fold_fixed<T, const N: usize>(
+    existing: Option<&[u8]>,
+    operands: &MergeOperands,
+    decode: fn([u8; N]) -> T,
+    encode: fn(T) -> [u8; N],
+    combine: fn(T, T) -> T,
+) -> Option<Vec<u8>> {
+    let mut values = existing.into_iter().chain(operands);
+    let mut result = decode(values.next()?.try_into().ok()?);
+
+    for bytes in values {
+        result = combine(result, decode(bytes.try_into().ok()?));
+    }
+
+    Some(encode(result).to_vec())
+}
+
+/// A table with a merge operation defined over decoded values.
+///
+/// Use [`MergeOperator::typed`] to create the corresponding byte-level merge operator.
+pub trait TypedMerge<M>: Table<M> {
+    fn merge_values(
+        existing: Option<Self::Value>,
+        operands: impl Iterator<Item = Self::Value>,
+    ) -> Self::Value;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocksdb::DB;
+
+    fn open(directory: &tempfile::TempDir, merge_operator: MergeOperator) -> DB {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        merge_operator.configure(&mut options);
+
+        DB::open(&options, directory.path()).unwrap()
+    }
+
+    #[test]
+    fn counters() {
+        let directory = tempfile::tempdir().unwrap();
+        let db = open(&directory, MergeOperator::add_i64());
+
+        db.put(b"a", 10i64.to_be_bytes()).unwrap();
+        db.merge(b"a", (-3i64).to_be_bytes()).unwrap();
+        db.merge(b"a", 5i64.to_be_bytes()).unwrap();
+        db.merge(b"b", (-1i64).to_be_bytes()).unwrap();
+
+        assert_eq!(db.get(b"a").unwrap(), Some(12i64.to_be_bytes().to_vec()));
+        assert_eq!(db.get(b"b").unwrap(), Some((-1i64).to_be_bytes().to_vec()));
+    }
+
+    #[test]
+    fn list_append() {
+        let directory = tempfile::tempdir().unwrap();
+        let db = open(&directory, MergeOperator::list_append(Some(3)));
+
+        db.put(b"a", encode_list(&[b"x".as_slice()])).unwrap();
+        db.merge(b"a", encode_list(&[b"y".as_slice(), b"zz"]))
+            .unwrap();
+        db.merge(b"a", encode_list(&[b"".as_slice()])).unwrap();
+        db.merge(b"b", encode_list(&[b"b".as_slice()])).unwrap();
+
+        let a = db.get(b"a").unwrap().unwrap();
+        let b = db.get(b"b").unwrap().unwrap();
+
+        assert_eq!(decode_list(&a).unwrap(), vec![b"y".as_slice(), b"zz", b""]);
+        assert_eq!(decode_list(&b).unwrap(), vec![b"b".as_slice()]);
+        assert!(decode_list(&[0, 0, 0, 2, 1]).is_err());
+    }
+
+    #[test]
+    fn set_union() {
+        let directory = tempfile::tempdir().unwrap();
+        let db = open(&directory, MergeOperator::set_union(8));
+
+        let encode = |ids: &[u64]| {
+            ids.iter()
+                .flat_map(|id| id.to_be_bytes())
+                .collect::<Vec<_>>()
+        };
+
+        db.put(b"a", encode(&[1, 5])).unwrap();
+        db.merge(b"a", encode(&[3, 5])).unwrap();
+        db.merge(b"a", encode(&[1, 100])).unwrap();
+
+        assert_eq!(db.get(b"a").unwrap(), Some(encode(&[1, 3, 5, 100])));
+    }
+
+    #[test]
+    #[should_panic(expected = "Item size must be positive")]
+    fn set_union_zero_item_size() {
+        MergeOperator::set_union(0);
+    }
+
+    #[test]
+    fn max() {
+        let directory = tempfile::tempdir().unwrap();
+        let db = open(&directory, MergeOperator::max_u64());
+
+        for value in [3u64, 10, 7] {
+            db.merge(b"a", value.to_be_bytes()).unwrap();
+        }
+
+        assert_eq!(db.get(b"a").unwrap(), Some(10u64.to_be_bytes().to_vec()));
+    }
+}
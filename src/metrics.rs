@@ -0,0 +1,99 @@
+//! Prometheus metrics for tables (requires the `metrics` feature).
+
+use crate::{error, Table};
+use prometheus::{IntCounterVec, IntGaugeVec, Opts, Registry};
+
+/// Integer properties that are exported as gauges, with their metric names.
+const PROPERTIES: [(&str, &str); 5] = [
+    ("rocksdb.estimate-num-keys", "rocksdb_estimate_num_keys"),
+    (
+        "rocksdb.size-all-mem-tables",
+        "rocksdb_size_all_mem_tables_bytes",
+    ),
+    (
+        "rocksdb.num-running-compactions",
+        "rocksdb_num_running_compactions",
+    ),
+    ("rocksdb.num-running-flushes", "rocksdb_num_running_flushes"),
+    (
+        "rocksdb.live-sst-files-size",
+        "rocksdb_live_sst_files_size_bytes",
+    ),
+];
+
+/// Tickers that are exported as counters (if statistics are enabled).
+const TICKERS: [&str; 7] = [
+    "rocksdb.block.cache.hit",
+    "rocksdb.block.cache.miss",
+    "rocksdb.bytes.read",
+    "rocksdb.bytes.written",
+    "rocksdb.compact.read.bytes",
+    "rocksdb.compact.write.bytes",
+    "rocksdb.stall.micros",
+];
+
+/// Exports RocksDB properties and tickers for tables, labeled by table name.
+///
+/// Values are sampled when [`MetricsCollector::update`] is called, which should happen
+/// periodically (or before each scrape).
+pub struct MetricsCollector {
+    properties: Vec<(&'static str, IntGaugeVec)>,
+    tickers: IntCounterVec,
+}
+
+impl MetricsCollector {
+    /// Create a collector and register its metrics.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let properties = PROPERTIES
+            .iter()
+            .map(|(property, name)| {
+                let gauge = IntGaugeVec::new(
+                    Opts::new(*name, format!("RocksDB property {property}")),
+                    &["table"],
+                )?;
+                registry.register(Box::new(gauge.clone()))?;
+
+                Ok((*property, gauge))
+            })
+            .collect::<Result<Vec<_>, prometheus::Error>>()?;
+
+        let tickers = IntCounterVec::new(
+            Opts::new("rocksdb_ticker_total", "RocksDB statistics ticker"),
+            &["table", "ticker"],
+        )?;
+        registry.register(Box::new(tickers.clone()))?;
+
+        Ok(Self {
+            properties,
+            tickers,
+        })
+    }
+
+    /// Sample the current values for a table.
+    pub fn update<M, T: Table<M>>(&self, table_name: &str, table: &T) -> Result<(), error::Error> {
+        let db = &table.database().db;
+
+        for (property, gauge) in &self.properties {
+            if let Some(value) = db.property_int_value(*property)? {
+                gauge
+                    .with_label_values(&[table_name])
+                    .set(value.try_into().unwrap_or(i64::MAX));
+            }
+        }
+
+        if let Some(statistics) = table.parsed_statistics() {
+            for ticker in TICKERS {
+                let counter = self.tickers.with_label_values(&[table_name, ticker]);
+                let current = counter.get();
+                let value = statistics.ticker(ticker);
+
+                // Counters can only increase, and tickers only decrease if statistics are reset.
+                if value > current {
+                    counter.inc_by(value - current);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
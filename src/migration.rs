@@ -0,0 +1,118 @@
+//! Versioned migrations, which are tracked in the schema metadata column family.
+
+use crate::{error, mode, schema, Table};
+use rocksdb::WriteBatch;
+use std::marker::PhantomData;
+
+/// The metadata key for the version of the last applied migration.
+const VERSION_KEY: &[u8] = b"migration_version";
+
+/// A change to a table's contents, identified by a version number.
+pub trait Migration<M, T: Table<M>> {
+    fn version(&self) -> u32;
+
+    /// Add the migration's changes to the batch (the table can be read but shouldn't be written
+    /// to directly, since the changes are written atomically with the new version).
+    fn migrate(&self, table: &T, batch: &mut MigrationBatch<M, T>) -> Result<(), T::Error>;
+}
+
+/// A batch of typed changes made by a migration.
+pub struct MigrationBatch<M, T> {
+    underlying: WriteBatch,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<M, T: Table<M>> MigrationBatch<M, T> {
+    pub fn put(&mut self, key: &T::Key, value: &T::Value) -> Result<(), T::Error> {
+        self.underlying
+            .put(T::key_to_bytes(key)?, T::value_to_bytes(value)?);
+
+        Ok(())
+    }
+
+    pub fn delete(&mut self, key: &T::Key) -> Result<(), T::Error> {
+        self.underlying.delete(T::key_to_bytes(key)?);
+
+        Ok(())
+    }
+
+    /// The number of changes in the batch.
+    pub fn len(&self) -> usize {
+        self.underlying.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.underlying.is_empty()
+    }
+}
+
+/// The version of the last migration applied to the table (if any).
+pub(crate) fn version<M, T: Table<M>>(table: &T) -> Result<Option<u32>, error::Error> {
+    let db = &table.database().db;
+
+    match db.cf_handle(schema::META_COLUMN_FAMILY_NAME) {
+        Some(meta_cf) => db
+            .get_cf(meta_cf, VERSION_KEY)?
+            .map(|bytes| {
+                bytes
+                    .try_into()
+                    .map(u32::from_be_bytes)
+                    .map_err(error::Error::InvalidValue)
+            })
+            .transpose(),
+        None => Ok(None),
+    }
+}
+
+/// Apply the migrations that are newer than the table's current version in order of version,
+/// writing each migration's changes together with its version, and return the versions applied.
+pub(crate) fn apply<M: mode::IsWriteable, T: Table<M>>(
+    table: &T,
+    migrations: &[&dyn Migration<M, T>],
+) -> Result<Vec<u32>, T::Error> {
+    let mut migrations = migrations.to_vec();
+    migrations.sort_by_key(|migration| migration.version());
+
+    if let Some(window) = migrations
+        .windows(2)
+        .find(|window| window[0].version() == window[1].version())
+    {
+        return Err(error::Error::InvalidConfiguration(format!(
+            "Duplicate migration version {}",
+            window[0].version()
+        ))
+        .into());
+    }
+
+    let db = &table.database().db;
+    let meta_cf = db
+        .cf_handle(schema::META_COLUMN_FAMILY_NAME)
+        .ok_or_else(|| {
+            error::Error::InvalidConfiguration("Missing schema metadata column family".to_string())
+        })?;
+    let current = version(table)?;
+    let mut applied = vec![];
+
+    for migration in migrations {
+        let version = migration.version();
+
+        if current.is_none_or(|current| version > current) {
+            let mut batch = MigrationBatch {
+                underlying: WriteBatch::default(),
+                _mode: PhantomData,
+                _table: PhantomData,
+            };
+
+            migration.migrate(table, &mut batch)?;
+            batch
+                .underlying
+                .put_cf(meta_cf, VERSION_KEY, version.to_be_bytes());
+            db.write(batch.underlying).map_err(error::Error::from)?;
+
+            applied.push(version);
+        }
+    }
+
+    Ok(applied)
+}
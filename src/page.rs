@@ -0,0 +1,72 @@
+//! Cursor-based pagination.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An opaque position in a table that a paged scan can be resumed from.
+///
+/// The string representation is a hex encoding of the last key returned, which makes it suitable
+/// for use as a resume token in URLs or JSON responses.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Cursor(Vec<u8>);
+
+impl Cursor {
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = crate::error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::error::Error::InvalidCursor(s.to_string());
+
+        if !s.len().is_multiple_of(2) || !s.is_ascii() {
+            return Err(invalid());
+        }
+
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+/// A page of decoded entries, with a cursor for the next page if there may be more entries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Page<K, V> {
+    pub entries: Vec<(K, V)>,
+    pub next: Option<Cursor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_string_round_trip() {
+        let cursor = Cursor::from_bytes(vec![0, 1, 127, 255]);
+        let encoded = cursor.to_string();
+
+        assert_eq!(encoded, "00017fff");
+        assert_eq!(encoded.parse::<Cursor>().unwrap(), cursor);
+        assert!("0".parse::<Cursor>().is_err());
+        assert!("zz".parse::<Cursor>().is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Parallel table access (requires the `rayon` feature).
+
+use crate::{error, mode, Entry, Table, TableIterator};
+use rayon::prelude::*;
+use rocksdb::{IteratorMode, ReadOptions, DEFAULT_COLUMN_FAMILY_NAME};
+use std::marker::PhantomData;
+
+/// A key range given by optional inclusive lower and exclusive upper bounds.
+type PartitionBounds = (Option<Vec<u8>>, Option<Vec<u8>>);
+
+type EntryResult<M, T> = Result<Entry<M, T>, <T as Table<M>>::Error>;
+
+/// Iterate over a table in parallel, splitting it into at most `partitions` key ranges.
+///
+/// Partition boundaries are sampled from the first keys of the table's SST files, so a table
+/// with fewer files (or with all of its data in memtables) will have fewer partitions. Collecting
+/// the returned iterator into a `Vec` preserves key order.
+pub fn par_iter<M: mode::Mode + Sync, T: Table<M> + Sync>(
+    table: &T,
+    partitions: usize,
+) -> Result<impl ParallelIterator<Item = EntryResult<M, T>> + '_, T::Error>
+where
+    T::Key: Send,
+    T::Value: Send,
+    T::Error: Send,
+{
+    let bounds = partition_bounds(table, partitions)?;
+
+    Ok(bounds.into_par_iter().flat_map_iter(move |(lower, upper)| {
+        let mut options = ReadOptions::default();
+
+        if let Some(lower) = lower {
+            options.set_iterate_lower_bound(lower);
+        }
+
+        if let Some(upper) = upper {
+            options.set_iterate_upper_bound(upper);
+        }
+
+        TableIterator::<M, T> {
+            underlying: table
+                .database()
+                .db
+                .iterator_opt(IteratorMode::Start, options),
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }))
+}
+
+/// Look up keys in parallel, splitting them into at most `max_threads` shards.
+///
+/// Each shard is looked up with a single [`Table::multi_lookup`] call, and values are returned
+/// in the same order as the keys.
+pub fn multi_lookup_parallel<M: mode::Mode + Sync, T: Table<M> + Sync>(
+    table: &T,
+    keys: &[T::Key],
+    max_threads: usize,
+) -> Result<Vec<Option<T::Value>>, T::Error>
+where
+    T::Key: Sync,
+    T::Value: Send,
+    T::Error: Send,
+{
+    let shard_size = keys.len().div_ceil(max_threads.max(1)).max(1);
+
+    let shards = keys
+        .par_chunks(shard_size)
+        .map(|shard| table.multi_lookup(shard))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(shards.into_iter().flatten().collect())
+}
+
+fn partition_bounds<M, T: Table<M>>(
+    table: &T,
+    partitions: usize,
+) -> Result<Vec<PartitionBounds>, error::Error> {
+    let mut candidates = table
+        .database()
+        .db
+        .live_files()?
+        .into_iter()
+        .filter(|live_file| live_file.column_family_name == DEFAULT_COLUMN_FAMILY_NAME)
+        .filter_map(|live_file| live_file.start_key)
+        .collect::<Vec<_>>();
+
+    candidates.sort();
+    candidates.dedup();
+
+    let mut boundaries = (1..partitions.max(1))
+        .filter_map(|i| candidates.get(i * candidates.len() / partitions).cloned())
+        .collect::<Vec<_>>();
+
+    // The first file's start key isn't a useful boundary.
+    boundaries.retain(|boundary| Some(boundary) != candidates.first());
+    boundaries.dedup();
+
+    let lowers = std::iter::once(None).chain(boundaries.iter().cloned().map(Some));
+    let uppers = boundaries
+        .iter()
+        .cloned()
+        .map(Some)
+        .chain(std::iter::once(None));
+
+    Ok(lowers.zip(uppers).collect())
+}
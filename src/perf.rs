@@ -0,0 +1,66 @@
+//! Per-operation performance counters.
+
+use rocksdb::perf::{set_perf_stats, PerfContext, PerfMetric, PerfStatsLevel};
+
+/// Selected counters from RocksDB's thread-local perf context (times are in nanoseconds).
+///
+/// Time counters are only collected at [`PerfStatsLevel::EnableTimeExceptForMutex`] or above.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PerfReport {
+    pub user_key_comparison_count: u64,
+    pub block_cache_hit_count: u64,
+    pub block_read_count: u64,
+    pub block_read_bytes: u64,
+    pub block_read_nanos: u64,
+    pub internal_key_skipped_count: u64,
+    pub internal_delete_skipped_count: u64,
+    pub get_from_memtable_nanos: u64,
+    pub get_from_output_files_nanos: u64,
+    pub seek_on_memtable_nanos: u64,
+    pub seek_child_seek_nanos: u64,
+    pub seek_internal_seek_nanos: u64,
+    pub find_next_user_entry_nanos: u64,
+    pub write_wal_nanos: u64,
+    pub write_memtable_nanos: u64,
+}
+
+impl PerfReport {
+    fn from_context(context: &PerfContext) -> Self {
+        Self {
+            user_key_comparison_count: context.metric(PerfMetric::UserKeyComparisonCount),
+            block_cache_hit_count: context.metric(PerfMetric::BlockCacheHitCount),
+            block_read_count: context.metric(PerfMetric::BlockReadCount),
+            block_read_bytes: context.metric(PerfMetric::BlockReadByte),
+            block_read_nanos: context.metric(PerfMetric::BlockReadTime),
+            internal_key_skipped_count: context.metric(PerfMetric::InternalKeySkippedCount),
+            internal_delete_skipped_count: context.metric(PerfMetric::InternalDeleteSkippedCount),
+            get_from_memtable_nanos: context.metric(PerfMetric::GetFromMemtableTime),
+            get_from_output_files_nanos: context.metric(PerfMetric::GetFromOutputFilesTime),
+            seek_on_memtable_nanos: context.metric(PerfMetric::SeekOnMemtableTime),
+            seek_child_seek_nanos: context.metric(PerfMetric::SeekChildSeekTime),
+            seek_internal_seek_nanos: context.metric(PerfMetric::SeekInternalSeekTime),
+            find_next_user_entry_nanos: context.metric(PerfMetric::FindNextUserEntryTime),
+            write_wal_nanos: context.metric(PerfMetric::WriteWalTime),
+            write_memtable_nanos: context.metric(PerfMetric::WriteMemtableTime),
+        }
+    }
+}
+
+/// Run a function with perf counters enabled at the given level, returning a report of the
+/// operations it performed on the current thread.
+///
+/// Operations on other threads (for example in background compactions) aren't counted, and perf
+/// counters are disabled for the current thread afterwards.
+pub fn with_perf_context<R, F: FnOnce() -> R>(level: PerfStatsLevel, f: F) -> (R, PerfReport) {
+    let mut context = PerfContext::default();
+
+    set_perf_stats(level);
+    context.reset();
+
+    let result = f();
+    let report = PerfReport::from_context(&context);
+
+    set_perf_stats(PerfStatsLevel::Disable);
+
+    (result, report)
+}
@@ -0,0 +1,121 @@
+//! Prefix extractor configuration.
+
+use rocksdb::{Options, SliceTransform};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Fixed,
+    Capped(fn(&[u8]) -> &[u8]),
+}
+
+/// A prefix extractor (slice transform) that can be installed on database options.
+///
+/// RocksDB uses the prefix extractor for prefix bloom filters and prefix seeks, which can make
+/// index lookups much cheaper when the index is a fixed-length key prefix.
+#[derive(Clone, Copy)]
+pub struct PrefixExtractor {
+    len: usize,
+    kind: Kind,
+}
+
+impl PrefixExtractor {
+    /// The first `len` bytes of each key.
+    ///
+    /// Keys shorter than `len` bytes aren't in the extractor's domain, so they're excluded from
+    /// prefix bloom filters.
+    pub fn fixed(len: usize) -> Self {
+        Self {
+            len,
+            kind: Kind::Fixed,
+        }
+    }
+
+    /// Up to the first `N` bytes of each key (so keys shorter than `N` bytes are their own prefix).
+    pub fn capped<const N: usize>() -> Self {
+        Self {
+            len: N,
+            kind: Kind::Capped(capped_prefix::<N>),
+        }
+    }
+
+    /// The (maximum) prefix length.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The name RocksDB records for this prefix extractor.
+    pub fn name(&self) -> String {
+        match self.kind {
+            Kind::Fixed => format!("rocksdb.FixedPrefix.{}", self.len),
+            Kind::Capped(_) => format!("rocksdb_table.CappedPrefix.{}", self.len),
+        }
+    }
+
+    /// The prefix of the given key.
+    pub fn transform<'a>(&self, key: &'a [u8]) -> &'a [u8] {
+        match self.kind {
+            Kind::Fixed => &key[..self.len.min(key.len())],
+            Kind::Capped(transform) => transform(key),
+        }
+    }
+
+    /// Install this prefix extractor on the given options.
+    pub fn configure(&self, options: &mut Options) {
+        let transform = match self.kind {
+            Kind::Fixed => SliceTransform::create_fixed_prefix(self.len),
+            Kind::Capped(transform) => SliceTransform::create(&self.name(), transform, None),
+        };
+
+        options.set_prefix_extractor(transform);
+    }
+}
+
+fn capped_prefix<const N: usize>(key: &[u8]) -> &[u8] {
+    &key[..N.min(key.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocksdb::{IteratorMode, ReadOptions, DB};
+
+    #[test]
+    fn capped() {
+        let extractor = PrefixExtractor::capped::<3>();
+
+        assert_eq!(extractor.transform(b"abcd"), b"abc");
+        assert_eq!(extractor.transform(b"ab"), b"ab");
+
+        let directory = tempfile::tempdir().unwrap();
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        extractor.configure(&mut options);
+
+        let db = DB::open(&options, directory.path()).unwrap();
+
+        for key in [b"a".as_slice(), b"ab", b"abc", b"abcd", b"abce", b"abd"] {
+            db.put(key, b"").unwrap();
+        }
+
+        let mut read_options = ReadOptions::default();
+        read_options.set_prefix_same_as_start(true);
+
+        let keys = db
+            .iterator_opt(
+                IteratorMode::From(b"abc", rocksdb::Direction::Forward),
+                read_options,
+            )
+            .map(|result| result.map(|(key, _)| key.to_vec()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            keys,
+            vec![b"abc".to_vec(), b"abcd".to_vec(), b"abce".to_vec()]
+        );
+    }
+}
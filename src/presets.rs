@@ -0,0 +1,183 @@
+//! Option presets for common table workloads.
+//!
+//! Each preset returns an options initialization function that can be passed to
+//! [`crate::Table::open`] directly, or applied after a table's own defaults:
+//!
+//! ```ignore
+//! Events::open(path, |options| presets::log_table(1 << 30)(Events::configure_options(options)))
+//! ```
+
+use rocksdb::{
+    BlockBasedOptions, DBCompactionStyle, DBCompressionType, FifoCompactOptions, Options,
+    UniversalCompactOptions,
+};
+use std::time::Duration;
+
+const MB: u64 = 1024 * 1024;
+
+/// A preset for append-mostly tables with a bounded total size, where the oldest data is dropped.
+///
+/// This uses FIFO compaction, so once the table's files exceed `max_table_size` bytes, the oldest
+/// files are deleted. All files stay in level 0, so compression is disabled to keep writes and
+/// reads of recent data cheap.
+pub fn log_table(max_table_size: u64) -> impl FnMut(Options) -> Options + Clone {
+    move |mut options| {
+        let mut fifo_options = FifoCompactOptions::default();
+        fifo_options.set_max_table_files_size(max_table_size);
+
+        options.set_compaction_style(DBCompactionStyle::Fifo);
+        options.set_fifo_compaction_options(&fifo_options);
+        options.set_compression_type(DBCompressionType::None);
+
+        // Keep individual memtables small relative to the table, so that dropping a file only
+        // removes a small fraction of the data.
+        options.set_write_buffer_size((max_table_size / 16).clamp(4 * MB, 64 * MB) as usize);
+
+        options
+    }
+}
+
+/// A preset for time-series tables, where writes mostly arrive in key order and reads are
+/// mostly range scans.
+///
+/// This uses universal compaction (which reduces write amplification for ordered inserts) and
+/// larger data blocks (which compress better and suit sequential reads).
+pub fn time_series() -> impl FnMut(Options) -> Options + Clone {
+    |mut options| {
+        let mut universal_options = UniversalCompactOptions::default();
+        universal_options.set_max_size_amplification_percent(200);
+
+        let mut block_options = BlockBasedOptions::default();
+        block_options.set_block_size(64 * 1024);
+
+        options.set_compaction_style(DBCompactionStyle::Universal);
+        options.set_universal_compaction_options(&universal_options);
+        options.set_block_based_table_factory(&block_options);
+        options.set_compression_type(DBCompressionType::Lz4);
+        options.set_write_buffer_size((128 * MB) as usize);
+
+        options
+    }
+}
+
+/// A preset for tables that are mostly read by exact key lookups (not scans), with a block cache
+/// of the given size.
+pub fn point_lookup(block_cache_size_mb: u64) -> impl FnMut(Options) -> Options + Clone {
+    move |mut options| {
+        options.optimize_for_point_lookup(block_cache_size_mb);
+
+        options
+    }
+}
+
+/// A preset for loading a large amount of data before the table is read.
+///
+/// Automatic compactions are disabled, so the table should be compacted manually (or reopened
+/// with different options) once loading is complete.
+pub fn bulk_load() -> impl FnMut(Options) -> Options + Clone {
+    |mut options| {
+        options.prepare_for_bulk_load();
+
+        options
+    }
+}
+
+/// A preset for tables with large values, which stores values of at least `min_blob_size` bytes
+/// in separate blob files (so that compactions don't need to rewrite them).
+///
+/// Blob garbage collection is enabled, so blob files are rewritten during compaction once they
+/// reach the age cutoff (the oldest quarter of blob files, by default).
+pub fn blob_values(min_blob_size: u64) -> impl FnMut(Options) -> Options + Clone {
+    move |mut options| {
+        options.set_enable_blob_files(true);
+        options.set_min_blob_size(min_blob_size);
+        options.set_blob_file_size(256 * MB);
+        options.set_blob_compression_type(DBCompressionType::Lz4);
+        options.set_enable_blob_gc(true);
+        options.set_blob_gc_age_cutoff(0.25);
+
+        options
+    }
+}
+
+/// A preset that limits the rate of background flush and compaction I/O to `bytes_per_sec`.
+///
+/// The limit is refilled every `refill_period`, and `fairness` controls how often low-priority
+/// requests are served before high-priority ones (RocksDB's default is 10). Writes made with
+/// [`crate::config::WriteConfig::low_pri`] are also throttled when compaction falls behind.
+pub fn rate_limited(
+    bytes_per_sec: u64,
+    refill_period: Duration,
+    fairness: i32,
+) -> impl FnMut(Options) -> Options + Clone {
+    move |mut options| {
+        options.set_ratelimiter(
+            bytes_per_sec.try_into().unwrap_or(i64::MAX),
+            refill_period.as_micros().try_into().unwrap_or(i64::MAX),
+            fairness,
+        );
+
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocksdb::DB;
+
+    fn open<F: FnMut(Options) -> Options>(
+        directory: &tempfile::TempDir,
+        mut options_init: F,
+    ) -> DB {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        DB::open(&options_init(options), directory.path()).unwrap()
+    }
+
+    #[test]
+    fn log_table() {
+        let directory = tempfile::tempdir().unwrap();
+        let db = open(&directory, super::log_table(64 * MB));
+
+        db.put(b"a", b"1").unwrap();
+        db.flush().unwrap();
+
+        assert_eq!(db.get(b"a").unwrap(), Some(b"1".to_vec()));
+    }
+
+    #[test]
+    fn presets() {
+        for mut options_init in [
+            Box::new(time_series()) as Box<dyn FnMut(Options) -> Options>,
+            Box::new(point_lookup(8)),
+            Box::new(bulk_load()),
+            Box::new(rate_limited(16 * MB, Duration::from_millis(100), 10)),
+        ] {
+            let directory = tempfile::tempdir().unwrap();
+            let db = open(&directory, &mut options_init);
+
+            db.put(b"a", b"1").unwrap();
+            db.flush().unwrap();
+
+            assert_eq!(db.get(b"a").unwrap(), Some(b"1".to_vec()));
+        }
+    }
+
+    #[test]
+    fn blob_values() {
+        let directory = tempfile::tempdir().unwrap();
+        let db = open(&directory, super::blob_values(1024));
+
+        db.put(b"small", [0; 16]).unwrap();
+        db.put(b"large", [0; 2048]).unwrap();
+        db.flush().unwrap();
+
+        assert_eq!(
+            db.property_int_value("rocksdb.num-blob-files").unwrap(),
+            Some(1)
+        );
+        assert_eq!(db.get(b"large").unwrap(), Some(vec![0; 2048]));
+    }
+}
@@ -0,0 +1,89 @@
+//! An untyped table for inspecting databases.
+
+use crate::{error::Error, mode, Database, Table};
+use std::borrow::Cow;
+
+/// A table with byte string keys and values, which can open any database that doesn't require a
+/// custom comparator.
+///
+/// Index lookups treat the index as a key prefix.
+pub struct RawTable<M> {
+    database: Database<M>,
+}
+
+impl<M: mode::Mode> Table<M> for RawTable<M> {
+    type Counts = u64;
+    type Error = Error;
+    type Key = Vec<u8>;
+    type KeyBytes = Vec<u8>;
+    type Value = Vec<u8>;
+    type ValueBytes = Vec<u8>;
+    type Index = Vec<u8>;
+    type IndexBytes = Vec<u8>;
+
+    fn database(&self) -> &Database<M> {
+        &self.database
+    }
+
+    fn from_database(database: Database<M>) -> Self {
+        Self { database }
+    }
+
+    fn get_counts(&self) -> Result<Self::Counts, Self::Error> {
+        self.exact_len()
+    }
+
+    fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
+        Ok(key.clone())
+    }
+
+    fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+        Ok(value.clone())
+    }
+
+    fn index_to_bytes(index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
+        Ok(index.clone())
+    }
+
+    fn encode_key_into(key: &Self::Key, buffer: &mut Vec<u8>) -> Result<(), Self::Error> {
+        buffer.extend_from_slice(key);
+        Ok(())
+    }
+
+    fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
+        Ok(bytes.into_owned())
+    }
+
+    fn bytes_to_value(bytes: Cow<[u8]>) -> Result<Self::Value, Self::Error> {
+        Ok(bytes.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_index() {
+        let directory = tempfile::tempdir().unwrap();
+        let table = RawTable::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        for key in [b"abc", b"abd", b"bcd"] {
+            table.put(&key.to_vec(), &key[1..].to_vec()).unwrap();
+        }
+
+        let entries = table
+            .lookup_index(&b"ab".to_vec())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                (b"abc".to_vec(), b"bc".to_vec()),
+                (b"abd".to_vec(), b"bd".to_vec())
+            ]
+        );
+        assert_eq!(table.get_counts().unwrap(), 3);
+    }
+}
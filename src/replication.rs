@@ -0,0 +1,134 @@
+//! Helpers for deployments with one writer process and many reader processes, where readers open
+//! the database in secondary mode and periodically catch up with the primary.
+
+use crate::{error::Error, mode, Table};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// The extension of write-ahead log files.
+const WAL_FILE_EXTENSION: &str = "log";
+
+/// A write-ahead log file in the primary's database directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalSegment {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// The replication state of a primary, which can be sent to followers so that they can report
+/// their lag.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrimaryStatus {
+    pub latest_sequence: u64,
+    /// Log files in the database directory (this doesn't include files in a separate WAL
+    /// directory), in order.
+    pub wal_segments: Vec<WalSegment>,
+}
+
+impl PrimaryStatus {
+    pub fn read<M: mode::IsWriteable, T: Table<M>>(table: &T) -> Result<Self, Error> {
+        let db = &table.database().db;
+        let latest_sequence = db.latest_sequence_number();
+        let mut wal_segments = vec![];
+
+        for entry in std::fs::read_dir(db.path())? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if path
+                .extension()
+                .is_some_and(|extension| extension == WAL_FILE_EXTENSION)
+            {
+                wal_segments.push(WalSegment {
+                    path,
+                    size_bytes: entry.metadata()?.len(),
+                });
+            }
+        }
+
+        wal_segments.sort_by(|left, right| left.path.cmp(&right.path));
+
+        Ok(Self {
+            latest_sequence,
+            wal_segments,
+        })
+    }
+}
+
+/// The result of a single catch-up.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReplicationProgress {
+    pub previous_sequence: u64,
+    pub sequence: u64,
+    /// How many sequence numbers the follower is behind the primary (if the primary's latest
+    /// sequence number is known).
+    pub lag: Option<u64>,
+    pub duration: Duration,
+}
+
+impl ReplicationProgress {
+    /// The number of sequence numbers applied by the catch-up.
+    pub fn applied(&self) -> u64 {
+        self.sequence.saturating_sub(self.previous_sequence)
+    }
+}
+
+/// A table opened in secondary mode that follows a primary.
+pub struct Follower<M, T> {
+    table: T,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::IsSecondary, T: Table<M>> Follower<M, T> {
+    pub fn new(table: T) -> Self {
+        Self {
+            table,
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    /// Catch up with the primary, given the primary's latest sequence number (if it's known).
+    pub fn catch_up(&self, primary_sequence: Option<u64>) -> Result<ReplicationProgress, T::Error> {
+        let start = Instant::now();
+        let db = &self.table.database().db;
+        let previous_sequence = db.latest_sequence_number();
+
+        self.table.catch_up_with_primary()?;
+
+        let sequence = db.latest_sequence_number();
+
+        Ok(ReplicationProgress {
+            previous_sequence,
+            sequence,
+            lag: primary_sequence.map(|primary_sequence| primary_sequence.saturating_sub(sequence)),
+            duration: start.elapsed(),
+        })
+    }
+
+    /// Catch up repeatedly, waiting for the given interval between catch-ups, until the
+    /// progress function returns `false` (the primary's latest sequence number is read before each
+    /// catch-up, and can be `None` if it's unknown).
+    pub fn follow<S, P>(
+        &self,
+        interval: Duration,
+        mut primary_sequence: S,
+        mut progress: P,
+    ) -> Result<(), T::Error>
+    where
+        S: FnMut() -> Option<u64>,
+        P: FnMut(&ReplicationProgress) -> bool,
+    {
+        loop {
+            if !progress(&self.catch_up(primary_sequence())?) {
+                return Ok(());
+            }
+
+            std::thread::sleep(interval);
+        }
+    }
+}
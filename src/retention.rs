@@ -0,0 +1,50 @@
+//! Retention policies for tables whose keys include a timestamp.
+
+use std::time::{Duration, SystemTime};
+
+/// A policy that determines which entries have expired, based on a timestamp taken from the key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Retention {
+    max_age: Duration,
+}
+
+impl Retention {
+    /// Expire entries whose timestamps are more than `max_age` before the current time.
+    pub fn older_than(max_age: Duration) -> Self {
+        Self { max_age }
+    }
+
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// Whether an entry with the given timestamp has expired at time `now`.
+    pub fn is_expired(&self, timestamp: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(timestamp)
+            .is_ok_and(|age| age > self.max_age)
+    }
+}
+
+/// Progress of a retention job (reported after each batch of deletions).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RetentionProgress {
+    /// The number of keys examined so far.
+    pub scanned: u64,
+    /// The number of expired entries deleted so far.
+    pub removed: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_expired() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let retention = Retention::older_than(Duration::from_secs(100));
+
+        assert!(retention.is_expired(now - Duration::from_secs(101), now));
+        assert!(!retention.is_expired(now - Duration::from_secs(100), now));
+        assert!(!retention.is_expired(now + Duration::from_secs(1), now));
+    }
+}
@@ -0,0 +1,141 @@
+//! Schema fingerprints, which are stored in a reserved column family so that changes to a table's
+//! encoding are detected when an existing database is opened (for tables that opt in via
+//! [`Table::schema_fingerprint`]).
+
+use crate::{error::Error, mode, Table};
+use rocksdb::{Options, DB, DEFAULT_COLUMN_FAMILY_NAME};
+
+/// The column family that stores fingerprints (keyed by the name of the table's column family).
+pub const META_COLUMN_FAMILY_NAME: &str = "__rocksdb_table_meta";
+
+/// A summary of the parts of a table's configuration that determine how its data is encoded.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Fingerprint {
+    pub column_family: String,
+    pub key_width: Option<usize>,
+    pub value_width: Option<usize>,
+    pub prefix_extractor: Option<String>,
+    pub merge_operator: Option<String>,
+    pub comparator: Option<String>,
+}
+
+impl Fingerprint {
+    /// The fingerprint for a table's default configuration.
+    pub fn of<M, T: Table<M>>() -> Self {
+        Self {
+            column_family: DEFAULT_COLUMN_FAMILY_NAME.to_string(),
+            key_width: T::key_width(),
+            value_width: T::value_width(),
+            prefix_extractor: T::prefix_extractor().map(|prefix_extractor| prefix_extractor.name()),
+            merge_operator: T::merge_operator()
+                .map(|merge_operator| merge_operator.name().to_string()),
+            comparator: T::comparator().map(|comparator| comparator.name().to_string()),
+        }
+    }
+
+    /// Encode the fingerprint as `name=value` lines (omitting missing values).
+    pub fn encode(&self) -> String {
+        let fields = [
+            ("column_family", Some(self.column_family.clone())),
+            ("key_width", self.key_width.map(|width| width.to_string())),
+            (
+                "value_width",
+                self.value_width.map(|width| width.to_string()),
+            ),
+            ("prefix_extractor", self.prefix_extractor.clone()),
+            ("merge_operator", self.merge_operator.clone()),
+            ("comparator", self.comparator.clone()),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| format!("{name}={value}\n")))
+            .collect()
+    }
+
+    /// Parse an encoded fingerprint (unknown or malformed lines are ignored).
+    pub fn parse(input: &str) -> Self {
+        let mut fingerprint = Self::default();
+
+        for (name, value) in input.lines().filter_map(|line| line.split_once('=')) {
+            match name {
+                "column_family" => fingerprint.column_family = value.to_string(),
+                "key_width" => fingerprint.key_width = value.parse().ok(),
+                "value_width" => fingerprint.value_width = value.parse().ok(),
+                "prefix_extractor" => fingerprint.prefix_extractor = Some(value.to_string()),
+                "merge_operator" => fingerprint.merge_operator = Some(value.to_string()),
+                "comparator" => fingerprint.comparator = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        fingerprint
+    }
+}
+
+/// Compare a newly opened database's stored fingerprint with the table's, storing the table's
+/// fingerprint if there isn't one yet (in write mode).
+///
+/// Nothing is read or written for tables that don't store fingerprints.
+pub(crate) fn check<M: mode::Mode, T: Table<M>>(db: &mut DB) -> Result<(), Error> {
+    if !T::schema_fingerprint() {
+        return Ok(());
+    }
+
+    let expected = Fingerprint::of::<M, T>();
+
+    let stored = match db.cf_handle(META_COLUMN_FAMILY_NAME) {
+        Some(meta_cf) => db.get_cf(meta_cf, &expected.column_family)?,
+        None => None,
+    };
+
+    match stored {
+        Some(stored_bytes) => {
+            let stored = Fingerprint::parse(&String::from_utf8_lossy(&stored_bytes));
+
+            if stored == expected {
+                Ok(())
+            } else {
+                Err(Error::SchemaMismatch {
+                    stored: Box::new(stored),
+                    expected: Box::new(expected),
+                })
+            }
+        }
+        None if M::is_primary() => {
+            if db.cf_handle(META_COLUMN_FAMILY_NAME).is_none() {
+                db.create_cf(META_COLUMN_FAMILY_NAME, &Options::default())?;
+            }
+
+            if let Some(meta_cf) = db.cf_handle(META_COLUMN_FAMILY_NAME) {
+                db.put_cf(meta_cf, &expected.column_family, expected.encode())?;
+            }
+
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_parse() {
+        let fingerprint = Fingerprint {
+            column_family: "default".to_string(),
+            key_width: Some(8),
+            value_width: None,
+            prefix_extractor: Some("rocksdb.FixedPrefix.4".to_string()),
+            merge_operator: Some("sum".to_string()),
+            comparator: None,
+        };
+
+        assert_eq!(
+            fingerprint.encode(),
+            "column_family=default\nkey_width=8\nprefix_extractor=rocksdb.FixedPrefix.4\nmerge_operator=sum\n"
+        );
+        assert_eq!(Fingerprint::parse(&fingerprint.encode()), fingerprint);
+    }
+}
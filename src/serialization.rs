@@ -0,0 +1,161 @@
+//! Value encodings based on Serde (requires the `serde` feature).
+//!
+//! Keys usually need an encoding that preserves their order under byte comparison, which general
+//! serialization formats don't guarantee, so these helpers are only intended for values. The
+//! [`crate::impl_value_codec_via_serde`] macro generates a table's value codec methods:
+//!
+//! ```ignore
+//! impl<M> Table<M> for Users<M> {
+//!     type Value = User;
+//!     type ValueBytes = Vec<u8>;
+//!
+//!     rocksdb_table::impl_value_codec_via_serde!(Bincode);
+//!
+//!     // Key and index methods...
+//! }
+//! ```
+
+use crate::error::Error;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A Serde data format for encoding values.
+pub trait Format {
+    fn encode<V: Serialize>(value: &V) -> Result<Vec<u8>, Error>;
+    fn decode<V: DeserializeOwned>(bytes: &[u8]) -> Result<V, Error>;
+}
+
+/// The [bincode](https://docs.rs/bincode) format.
+pub struct Bincode;
+
+/// The [postcard](https://docs.rs/postcard) format (which is generally more compact).
+pub struct Postcard;
+
+impl Format for Bincode {
+    fn encode<V: Serialize>(value: &V) -> Result<Vec<u8>, Error> {
+        bincode::serialize(value).map_err(|error| Error::ValueEncoding(error.to_string()))
+    }
+
+    fn decode<V: DeserializeOwned>(bytes: &[u8]) -> Result<V, Error> {
+        bincode::deserialize(bytes).map_err(|_| Error::InvalidValue(bytes.to_vec()))
+    }
+}
+
+impl Format for Postcard {
+    fn encode<V: Serialize>(value: &V) -> Result<Vec<u8>, Error> {
+        postcard::to_stdvec(value).map_err(|error| Error::ValueEncoding(error.to_string()))
+    }
+
+    fn decode<V: DeserializeOwned>(bytes: &[u8]) -> Result<V, Error> {
+        postcard::from_bytes(bytes).map_err(|_| Error::InvalidValue(bytes.to_vec()))
+    }
+}
+
+/// Generate `value_to_bytes` and `bytes_to_value` for a table implementation, using one of the
+/// formats in [`crate::serialization`] (the table's `ValueBytes` must be `Vec<u8>`).
+#[macro_export]
+macro_rules! impl_value_codec_via_serde {
+    ($format:ident) => {
+        #[allow(clippy::needless_question_mark)]
+        fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+            Ok(<$crate::serialization::$format as $crate::serialization::Format>::encode(value)?)
+        }
+
+        #[allow(clippy::needless_question_mark)]
+        fn bytes_to_value(bytes: std::borrow::Cow<[u8]>) -> Result<Self::Value, Self::Error> {
+            Ok(<$crate::serialization::$format as $crate::serialization::Format>::decode(&bytes)?)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mode, Database, Table};
+    use serde::Deserialize;
+    use std::borrow::Cow;
+
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    struct User {
+        screen_name: String,
+        followers: Vec<u64>,
+    }
+
+    struct Users<M> {
+        database: Database<M>,
+    }
+
+    impl<M> Table<M> for Users<M> {
+        type Counts = ();
+        type Error = Error;
+        type Key = u64;
+        type KeyBytes = [u8; 8];
+        type Value = User;
+        type ValueBytes = Vec<u8>;
+        type Index = ();
+        type IndexBytes = [u8; 0];
+
+        fn database(&self) -> &Database<M> {
+            &self.database
+        }
+
+        fn from_database(database: Database<M>) -> Self {
+            Self { database }
+        }
+
+        fn get_counts(&self) -> Result<Self::Counts, Self::Error> {
+            Ok(())
+        }
+
+        fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
+            Ok(key.to_be_bytes())
+        }
+
+        fn index_to_bytes(_index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
+            Ok([])
+        }
+
+        fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
+            Ok(u64::from_be_bytes(
+                bytes
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| Error::InvalidKey(bytes.to_vec()))?,
+            ))
+        }
+
+        crate::impl_value_codec_via_serde!(Postcard);
+    }
+
+    #[test]
+    fn round_trip() {
+        let user = User {
+            screen_name: "foo".to_string(),
+            followers: vec![1, 2, 3],
+        };
+
+        assert_eq!(
+            Bincode::decode::<User>(&Bincode::encode(&user).unwrap()).unwrap(),
+            user
+        );
+        assert_eq!(
+            Postcard::decode::<User>(&Postcard::encode(&user).unwrap()).unwrap(),
+            user
+        );
+        assert!(Postcard::decode::<User>(&[255]).is_err());
+    }
+
+    #[test]
+    fn table() {
+        let directory = tempfile::tempdir().unwrap();
+        let users = Users::<mode::Writeable>::open_with_defaults(directory).unwrap();
+
+        let user = User {
+            screen_name: "foo".to_string(),
+            followers: vec![1, 2, 3],
+        };
+
+        users.put(&123, &user).unwrap();
+
+        assert_eq!(users.lookup_key(&123).unwrap(), Some(user));
+    }
+}
@@ -0,0 +1,205 @@
+//! Soft deletion, where deleted keys are marked with tombstone values that are removed during
+//! compaction after a grace period.
+
+use crate::{compaction, error, mode, Table};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The start of every tombstone value (which is followed by the deletion time).
+const TOMBSTONE_MARKER: &[u8] = b"\xffrocksdb_table.tombstone\xff";
+
+/// A policy for removing tombstones, which is installed as part of a table's compaction filter
+/// when it's returned by [`Table::soft_delete`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SoftDelete {
+    /// How long tombstones are kept after the deletion (for example so that they can be sent to
+    /// replicas).
+    pub grace_period: Duration,
+}
+
+impl SoftDelete {
+    /// A compaction filter that removes expired tombstones, and passes other entries to the given
+    /// filter (if any).
+    pub fn compaction_filter(
+        self,
+        inner: Option<compaction::CompactionFilter>,
+    ) -> compaction::CompactionFilter {
+        let name = match &inner {
+            Some(inner) => format!("{}+rocksdb_table.SoftDelete", inner.name()),
+            None => "rocksdb_table.SoftDelete".to_string(),
+        };
+
+        compaction::CompactionFilter::new(&name, move |level, key_bytes, value_bytes| {
+            match tombstone_time(value_bytes) {
+                Some(deleted_at) => {
+                    if SystemTime::now()
+                        .duration_since(deleted_at)
+                        .is_ok_and(|age| age > self.grace_period)
+                    {
+                        compaction::FilterDecision::Remove
+                    } else {
+                        compaction::FilterDecision::Keep
+                    }
+                }
+                None => inner
+                    .as_ref()
+                    .map_or(compaction::FilterDecision::Keep, |inner| {
+                        inner.apply(level, key_bytes, value_bytes)
+                    }),
+            }
+        })
+    }
+}
+
+/// A tombstone value for a deletion at the given time.
+pub fn tombstone(deleted_at: SystemTime) -> Vec<u8> {
+    let micros = deleted_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+
+    let mut bytes = Vec::with_capacity(TOMBSTONE_MARKER.len() + 8);
+    bytes.extend_from_slice(TOMBSTONE_MARKER);
+    bytes.extend_from_slice(&micros.to_be_bytes());
+    bytes
+}
+
+/// The deletion time of a tombstone value (or `None` if the value isn't a tombstone).
+pub fn tombstone_time(value_bytes: &[u8]) -> Option<SystemTime> {
+    let micros = value_bytes
+        .strip_prefix(TOMBSTONE_MARKER)?
+        .try_into()
+        .ok()?;
+
+    Some(UNIX_EPOCH + Duration::from_micros(u64::from_be_bytes(micros)))
+}
+
+/// A wrapper for a table where deletions write tombstones, and lookups and iteration skip keys
+/// with tombstones.
+///
+/// Table values must never have the same encoding as a tombstone (a marker that starts with a
+/// `0xff` byte, followed by a timestamp). Tombstones are only removed if the table's
+/// [`Table::soft_delete`] policy is set.
+pub struct SoftDeleteTable<M, T> {
+    table: T,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::Mode, T: Table<M>> SoftDeleteTable<M, T> {
+    pub fn new(table: T) -> Self {
+        Self {
+            table,
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub fn lookup_key(&self, key: &T::Key) -> Result<Option<T::Value>, T::Error> {
+        let key_bytes = T::key_to_bytes(key)?;
+
+        match self
+            .table
+            .database()
+            .db
+            .get_pinned(key_bytes)
+            .map_err(error::Error::from)?
+        {
+            Some(value_bytes) if tombstone_time(&value_bytes).is_none() => {
+                T::bytes_to_value(Cow::from(value_bytes.as_ref())).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The time the key was deleted, if it has a tombstone.
+    pub fn deleted_at(&self, key: &T::Key) -> Result<Option<SystemTime>, T::Error> {
+        let key_bytes = T::key_to_bytes(key)?;
+
+        Ok(self
+            .table
+            .database()
+            .db
+            .get_pinned(key_bytes)
+            .map_err(error::Error::from)?
+            .and_then(|value_bytes| tombstone_time(&value_bytes)))
+    }
+
+    pub fn put(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.table.put(key, value)
+    }
+
+    /// Replace the key's value with a tombstone.
+    pub fn delete(&self, key: &T::Key) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        let key_bytes = T::key_to_bytes(key)?;
+
+        Ok(self
+            .table
+            .database()
+            .db
+            .put(key_bytes, tombstone(SystemTime::now()))
+            .map_err(error::Error::from)?)
+    }
+
+    /// Iterate over the entries that haven't been deleted.
+    pub fn iter(&self) -> impl Iterator<Item = Result<crate::Entry<M, T>, T::Error>> + '_ {
+        self.table
+            .database()
+            .db
+            .iterator(rocksdb::IteratorMode::Start)
+            .filter_map(|result| match result {
+                Ok((key_bytes, value_bytes)) => {
+                    if tombstone_time(&value_bytes).is_some() {
+                        None
+                    } else {
+                        Some(
+                            T::bytes_to_key(Cow::from(Vec::from(key_bytes))).and_then(|key| {
+                                T::bytes_to_value(Cow::from(Vec::from(value_bytes)))
+                                    .map(|value| (key, value))
+                            }),
+                        )
+                    }
+                }
+                Err(error) => Some(Err(error::Error::from(error).into())),
+            })
+    }
+
+    /// Iterate over the keys that have tombstones, with their deletion times.
+    pub fn iter_deleted(
+        &self,
+    ) -> impl Iterator<Item = Result<(T::Key, SystemTime), T::Error>> + '_ {
+        self.table
+            .database()
+            .db
+            .iterator(rocksdb::IteratorMode::Start)
+            .filter_map(|result| match result {
+                Ok((key_bytes, value_bytes)) => tombstone_time(&value_bytes).map(|deleted_at| {
+                    T::bytes_to_key(Cow::from(Vec::from(key_bytes))).map(|key| (key, deleted_at))
+                }),
+                Err(error) => Some(Err(error::Error::from(error).into())),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tombstone_round_trip() {
+        let deleted_at = UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456);
+
+        assert_eq!(tombstone_time(&tombstone(deleted_at)), Some(deleted_at));
+        assert_eq!(tombstone_time(b"value"), None);
+        assert_eq!(tombstone_time(TOMBSTONE_MARKER), None);
+    }
+}
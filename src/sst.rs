@@ -0,0 +1,166 @@
+//! Writing typed entries to SST files for external file ingestion.
+
+use crate::{comparator::Comparator, error, Table};
+use rocksdb::{DBRawIterator, Options, SstFileWriter};
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// The size at which [`write_files`] starts a new file.
+const MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A writer for an SST file containing entries for a table.
+///
+/// Entries must be added in strictly increasing key order, as defined by the table's comparator
+/// (or by the encoded keys' bytes if it doesn't have one). Out-of-order keys are rejected with
+/// [`error::Error::OutOfOrderKey`]. The finished file can be loaded with [`Table::ingest`].
+pub struct SstWriter<'a, M, T> {
+    underlying: SstFileWriter<'a>,
+    comparator: Option<Comparator>,
+    last_key_bytes: Option<Vec<u8>>,
+    key_buffer: Vec<u8>,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<'a, M, T: Table<M>> SstWriter<'a, M, T> {
+    /// Create a writer for a new file at the given path, using the given table options.
+    pub fn create<P: AsRef<Path>>(options: &'a Options, path: P) -> Result<Self, T::Error> {
+        let underlying = SstFileWriter::create(options);
+        underlying.open(path).map_err(error::Error::from)?;
+
+        Ok(Self {
+            underlying,
+            comparator: T::comparator(),
+            last_key_bytes: None,
+            key_buffer: vec![],
+            _mode: PhantomData,
+            _table: PhantomData,
+        })
+    }
+
+    pub fn put(&mut self, key: &T::Key, value: &T::Value) -> Result<(), T::Error> {
+        self.key_buffer.clear();
+        T::encode_key_into(key, &mut self.key_buffer)?;
+        let value_bytes = T::value_to_bytes(value)?;
+
+        if let Some(last_key_bytes) = &self.last_key_bytes {
+            let ordering = match &self.comparator {
+                Some(comparator) => comparator.compare(&self.key_buffer, last_key_bytes),
+                None => self.key_buffer.as_slice().cmp(last_key_bytes),
+            };
+
+            if ordering != Ordering::Greater {
+                return Err(error::Error::OutOfOrderKey(self.key_buffer.clone()).into());
+            }
+        }
+
+        self.underlying
+            .put(&self.key_buffer, value_bytes)
+            .map_err(error::Error::from)?;
+
+        // The previous key's buffer is reused for the next key.
+        match &mut self.last_key_bytes {
+            Some(last_key_bytes) => std::mem::swap(last_key_bytes, &mut self.key_buffer),
+            None => self.last_key_bytes = Some(std::mem::take(&mut self.key_buffer)),
+        }
+
+        Ok(())
+    }
+
+    /// The current size of the file in bytes.
+    pub fn file_size(&self) -> u64 {
+        self.underlying.file_size()
+    }
+
+    /// Finish writing the file (this must be called before the file is ingested).
+    pub fn finish(mut self) -> Result<(), T::Error> {
+        Ok(self.underlying.finish().map_err(error::Error::from)?)
+    }
+}
+
+/// Write sorted entries to SST files in the given directory, starting a new file whenever the
+/// current one gets too large, and return the paths of the files.
+pub(crate) fn write_files<M, T: Table<M>, I: IntoIterator<Item = crate::Entry<M, T>>>(
+    options: &Options,
+    directory: &Path,
+    entries: I,
+) -> Result<Vec<PathBuf>, T::Error> {
+    let mut paths = vec![];
+    let mut writer: Option<SstWriter<'_, M, T>> = None;
+    let mut last_key_bytes = None;
+
+    for (key, value) in entries {
+        let mut current = match writer.take() {
+            Some(writer) => writer,
+            None => {
+                let path = directory.join(format!("{:06}.sst", paths.len()));
+                let mut writer = SstWriter::<M, T>::create(options, &path)?;
+                // Keys must also be ordered across files.
+                writer.last_key_bytes = last_key_bytes.take();
+                paths.push(path);
+                writer
+            }
+        };
+
+        current.put(&key, &value)?;
+
+        if current.file_size() >= MAX_FILE_SIZE {
+            last_key_bytes = current.last_key_bytes.take();
+            current.finish()?;
+        } else {
+            writer = Some(current);
+        }
+    }
+
+    if let Some(writer) = writer {
+        writer.finish()?;
+    }
+
+    Ok(paths)
+}
+
+/// Write the remaining entries of a raw iterator to SST files in the given directory (starting
+/// new files in the same way as [`write_files`]), and return the paths of the files and the
+/// number of entries.
+pub(crate) fn write_raw_files(
+    options: &Options,
+    directory: &Path,
+    underlying: &mut DBRawIterator<'_>,
+) -> Result<(Vec<PathBuf>, u64), error::Error> {
+    let mut paths = vec![];
+    let mut count = 0;
+    let mut writer: Option<SstFileWriter<'_>> = None;
+
+    while let (Some(key_bytes), Some(value_bytes)) = (underlying.key(), underlying.value()) {
+        let current = match &mut writer {
+            Some(writer) => writer,
+            None => {
+                let path = directory.join(format!("{:06}.sst", paths.len()));
+                let new_writer = SstFileWriter::create(options);
+                new_writer.open(&path)?;
+                paths.push(path);
+                writer.insert(new_writer)
+            }
+        };
+
+        current.put(key_bytes, value_bytes)?;
+        count += 1;
+
+        if current.file_size() >= MAX_FILE_SIZE {
+            if let Some(mut writer) = writer.take() {
+                writer.finish()?;
+            }
+        }
+
+        underlying.next();
+    }
+
+    underlying.status()?;
+
+    if let Some(mut writer) = writer {
+        writer.finish()?;
+    }
+
+    Ok((paths, count))
+}
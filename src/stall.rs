@@ -0,0 +1,104 @@
+//! Detection of write stalls, for slowing down bulk writes.
+
+use crate::{error, Database};
+use std::time::{Duration, Instant};
+
+/// Stall-related properties of a database.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StallState {
+    /// The rate (in bytes per second) that writes are currently delayed to (zero if they aren't).
+    pub delayed_write_rate: u64,
+    pub is_write_stopped: bool,
+    pub pending_compaction_bytes: u64,
+}
+
+impl StallState {
+    pub fn read<M>(database: &Database<M>) -> Result<Self, error::Error> {
+        let property = |name: &str| {
+            database
+                .db
+                .property_int_value(name)
+                .map(Option::unwrap_or_default)
+        };
+
+        Ok(Self {
+            delayed_write_rate: property("rocksdb.actual-delayed-write-rate")?,
+            is_write_stopped: property("rocksdb.is-write-stopped")? != 0,
+            pending_compaction_bytes: property("rocksdb.estimate-pending-compaction-bytes")?,
+        })
+    }
+}
+
+/// A policy for pausing bulk writes while a database is stalling.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Backpressure {
+    /// Writes also pause when compaction falls this far behind (this should be lower than the
+    /// database's soft pending compaction bytes limit, so that writes pause before they're delayed).
+    pub max_pending_compaction_bytes: u64,
+    pub poll_interval: Duration,
+    /// The longest time to pause before writing anyway.
+    pub max_wait: Duration,
+}
+
+impl Default for Backpressure {
+    fn default() -> Self {
+        Self {
+            max_pending_compaction_bytes: 32 * 1024 * 1024 * 1024,
+            poll_interval: Duration::from_millis(100),
+            max_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backpressure {
+    pub fn should_wait(&self, state: &StallState) -> bool {
+        state.is_write_stopped
+            || state.delayed_write_rate > 0
+            || state.pending_compaction_bytes > self.max_pending_compaction_bytes
+    }
+
+    /// Wait until the database isn't stalling (or the maximum wait has passed), returning the time
+    /// spent waiting.
+    pub fn wait<M>(&self, database: &Database<M>) -> Result<Duration, error::Error> {
+        let start = Instant::now();
+
+        while self.should_wait(&StallState::read(database)?) {
+            let elapsed = start.elapsed();
+
+            if elapsed >= self.max_wait {
+                return Ok(elapsed);
+            }
+
+            std::thread::sleep(self.poll_interval.min(self.max_wait - elapsed));
+        }
+
+        Ok(start.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_wait() {
+        let backpressure = Backpressure {
+            max_pending_compaction_bytes: 1000,
+            ..Backpressure::default()
+        };
+
+        assert!(!backpressure.should_wait(&StallState::default()));
+        assert!(backpressure.should_wait(&StallState {
+            delayed_write_rate: 16 * 1024 * 1024,
+            ..StallState::default()
+        }));
+        assert!(backpressure.should_wait(&StallState {
+            is_write_stopped: true,
+            ..StallState::default()
+        }));
+        assert!(backpressure.should_wait(&StallState {
+            pending_compaction_bytes: 1001,
+            ..StallState::default()
+        }));
+    }
+}
@@ -0,0 +1,155 @@
+//! Parsing of RocksDB statistics.
+//!
+//! Statistics are only collected if they're enabled in the table's options (with
+//! [`rocksdb::Options::enable_statistics`]).
+
+use std::collections::HashMap;
+
+/// A summary of a histogram's distribution.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Histogram {
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub p100: f64,
+    pub count: u64,
+    pub sum: u64,
+}
+
+/// Ticker and histogram values parsed from RocksDB's statistics dump.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub tickers: HashMap<String, u64>,
+    pub histograms: HashMap<String, Histogram>,
+}
+
+impl Statistics {
+    /// Parse the output of [`rocksdb::Options::get_statistics`] (lines that can't be parsed are
+    /// ignored).
+    pub fn parse(input: &str) -> Self {
+        let mut statistics = Self::default();
+
+        for line in input.lines() {
+            let mut parts = line.split_whitespace();
+
+            if let Some(name) = parts.next() {
+                let mut fields = HashMap::new();
+
+                while let (Some(field), Some(":"), Some(value)) =
+                    (parts.next(), parts.next(), parts.next())
+                {
+                    fields.insert(field, value);
+                }
+
+                if fields.len() == 1 {
+                    if let Some(count) = fields.get("COUNT").and_then(|value| value.parse().ok()) {
+                        statistics.tickers.insert(name.to_string(), count);
+                    }
+                } else if let Some(histogram) = parse_histogram(&fields) {
+                    statistics.histograms.insert(name.to_string(), histogram);
+                }
+            }
+        }
+
+        statistics
+    }
+
+    /// The value of a ticker (zero if it isn't present).
+    pub fn ticker(&self, name: &str) -> u64 {
+        self.tickers.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn histogram(&self, name: &str) -> Option<&Histogram> {
+        self.histograms.get(name)
+    }
+
+    pub fn block_cache_hits(&self) -> u64 {
+        self.ticker("rocksdb.block.cache.hit")
+    }
+
+    pub fn block_cache_misses(&self) -> u64 {
+        self.ticker("rocksdb.block.cache.miss")
+    }
+
+    /// The proportion of block cache lookups that were hits (if there were any lookups).
+    pub fn block_cache_hit_rate(&self) -> Option<f64> {
+        let hits = self.block_cache_hits();
+        let total = hits + self.block_cache_misses();
+
+        (total > 0).then(|| hits as f64 / total as f64)
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.ticker("rocksdb.bytes.read")
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.ticker("rocksdb.bytes.written")
+    }
+
+    pub fn compaction_bytes_read(&self) -> u64 {
+        self.ticker("rocksdb.compact.read.bytes")
+    }
+
+    pub fn compaction_bytes_written(&self) -> u64 {
+        self.ticker("rocksdb.compact.write.bytes")
+    }
+
+    /// The total time that writes have been stalled, in microseconds.
+    pub fn stall_micros(&self) -> u64 {
+        self.ticker("rocksdb.stall.micros")
+    }
+
+    pub fn get_micros(&self) -> Option<&Histogram> {
+        self.histogram("rocksdb.db.get.micros")
+    }
+
+    pub fn write_micros(&self) -> Option<&Histogram> {
+        self.histogram("rocksdb.db.write.micros")
+    }
+}
+
+fn parse_histogram(fields: &HashMap<&str, &str>) -> Option<Histogram> {
+    Some(Histogram {
+        p50: fields.get("P50")?.parse().ok()?,
+        p95: fields.get("P95")?.parse().ok()?,
+        p99: fields.get("P99")?.parse().ok()?,
+        p100: fields.get("P100")?.parse().ok()?,
+        count: fields.get("COUNT")?.parse().ok()?,
+        sum: fields.get("SUM")?.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let input = "rocksdb.block.cache.miss COUNT : 10
+rocksdb.block.cache.hit COUNT : 30
+rocksdb.stall.micros COUNT : 1234
+rocksdb.db.get.micros P50 : 1.500000 P95 : 3.000000 P99 : 4.250000 P100 : 12.000000 COUNT : 40 SUM : 90
+unparseable line
+";
+
+        let statistics = Statistics::parse(input);
+
+        assert_eq!(statistics.tickers.len(), 3);
+        assert_eq!(statistics.block_cache_hit_rate(), Some(0.75));
+        assert_eq!(statistics.stall_micros(), 1234);
+        assert_eq!(statistics.compaction_bytes_written(), 0);
+        assert_eq!(
+            statistics.get_micros(),
+            Some(&Histogram {
+                p50: 1.5,
+                p95: 3.0,
+                p99: 4.25,
+                p100: 12.0,
+                count: 40,
+                sum: 90
+            })
+        );
+        assert_eq!(statistics.write_micros(), None);
+    }
+}
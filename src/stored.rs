@@ -0,0 +1,107 @@
+//! Options recorded in an existing database's OPTIONS file.
+
+use crate::error::Error;
+use std::path::Path;
+
+const OPTIONS_FILE_PREFIX: &str = "OPTIONS-";
+const DEFAULT_CF_SECTION: &str = "[CFOptions \"default\"]";
+
+/// The names of the customizable components used by the default column family of a database.
+///
+/// RocksDB checks that the comparator matches when a database is opened, but a database can be
+/// opened with a different merge operator or prefix extractor, which can silently break merges
+/// or prefix iteration.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StoredOptions {
+    pub comparator: Option<String>,
+    pub merge_operator: Option<String>,
+    pub compaction_filter: Option<String>,
+    pub prefix_extractor: Option<String>,
+}
+
+impl StoredOptions {
+    /// Load the options from the latest OPTIONS file in a database directory (if there is one).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Option<Self>, Error> {
+        let mut latest = None;
+
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let number = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(OPTIONS_FILE_PREFIX))
+                .and_then(|number| number.parse::<u64>().ok());
+
+            if let Some(number) = number {
+                if latest
+                    .as_ref()
+                    .is_none_or(|(latest_number, _)| number > *latest_number)
+                {
+                    latest = Some((number, entry.path()));
+                }
+            }
+        }
+
+        latest
+            .map(|(_, path)| Ok(Self::parse(&std::fs::read_to_string(path)?)))
+            .transpose()
+    }
+
+    /// Parse the contents of an OPTIONS file.
+    pub fn parse(input: &str) -> Self {
+        let mut options = Self::default();
+        let mut in_default_cf = false;
+
+        for line in input.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_default_cf = line == DEFAULT_CF_SECTION;
+            } else if in_default_cf {
+                if let Some((name, value)) = line.split_once('=') {
+                    let value = Some(value.to_string()).filter(|value| value != "nullptr");
+
+                    match name {
+                        "comparator" => options.comparator = value,
+                        "merge_operator" => options.merge_operator = value,
+                        "compaction_filter" => options.compaction_filter = value,
+                        "prefix_extractor" => options.prefix_extractor = value,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let input = "[Version]
+  rocksdb_version=8.10.0
+
+[CFOptions \"default\"]
+  prefix_extractor=rocksdb.FixedPrefix.3
+  memtable_insert_with_hint_prefix_extractor=nullptr
+  merge_operator=sum
+  compaction_filter=nullptr
+  comparator=leveldb.BytewiseComparator
+
+[CFOptions \"other\"]
+  merge_operator=other
+";
+
+        assert_eq!(
+            StoredOptions::parse(input),
+            StoredOptions {
+                comparator: Some("leveldb.BytewiseComparator".to_string()),
+                merge_operator: Some("sum".to_string()),
+                compaction_filter: None,
+                prefix_extractor: Some("rocksdb.FixedPrefix.3".to_string()),
+            }
+        );
+    }
+}
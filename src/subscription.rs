@@ -0,0 +1,132 @@
+//! Subscriptions to changes made through a table handle.
+
+use crate::{mode, Table};
+use std::marker::PhantomData;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, MutexGuard};
+
+/// A change made to a table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChangeEvent<K, V> {
+    Put { key: K, value: V },
+    Merge { key: K, operand: V },
+    Delete { key: K },
+}
+
+impl<K, V> ChangeEvent<K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Put { key, .. } | Self::Merge { key, .. } | Self::Delete { key } => key,
+        }
+    }
+}
+
+/// A receiver for a subscription to a table's changes.
+pub type ChangeReceiver<M, T> = Receiver<ChangeEvent<<T as Table<M>>::Key, <T as Table<M>>::Value>>;
+
+struct Subscriber<K, V> {
+    prefix: Vec<u8>,
+    sender: Sender<ChangeEvent<K, V>>,
+}
+
+/// A wrapper for a table that sends changes made through it to subscribers.
+///
+/// Each subscriber receives the changes to keys that start with its index (after the change has
+/// been written). Changes made through the underlying table (or any other handle to the same
+/// database) aren't sent. Subscribers are removed when their receivers are dropped.
+pub struct SubscribableTable<M, T: Table<M>> {
+    table: T,
+    subscribers: Mutex<Vec<Subscriber<T::Key, T::Value>>>,
+    _mode: PhantomData<M>,
+}
+
+impl<M: mode::Mode, T: Table<M>> SubscribableTable<M, T>
+where
+    T::Key: Clone,
+    T::Value: Clone,
+{
+    pub fn new(table: T) -> Self {
+        Self {
+            table,
+            subscribers: Mutex::new(vec![]),
+            _mode: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    /// The number of active subscribers (as of the last change).
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers().len()
+    }
+
+    /// Subscribe to every change.
+    pub fn subscribe(&self) -> ChangeReceiver<M, T> {
+        self.subscribe_prefix(vec![])
+    }
+
+    /// Subscribe to changes to keys with the given index.
+    pub fn subscribe_index(&self, index: &T::Index) -> Result<ChangeReceiver<M, T>, T::Error> {
+        let index_bytes = T::index_to_bytes(index)?;
+
+        Ok(self.subscribe_prefix(index_bytes.as_ref().to_vec()))
+    }
+
+    pub fn put(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.table.put(key, value)?;
+        self.notify(ChangeEvent::Put {
+            key: key.clone(),
+            value: value.clone(),
+        })
+    }
+
+    pub fn merge(&self, key: &T::Key, value: &T::Value) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.table.merge(key, value)?;
+        self.notify(ChangeEvent::Merge {
+            key: key.clone(),
+            operand: value.clone(),
+        })
+    }
+
+    pub fn delete(&self, key: &T::Key) -> Result<(), T::Error>
+    where
+        M: mode::IsWriteable,
+    {
+        self.table.delete(key)?;
+        self.notify(ChangeEvent::Delete { key: key.clone() })
+    }
+
+    fn subscribe_prefix(&self, prefix: Vec<u8>) -> ChangeReceiver<M, T> {
+        let (sender, receiver) = channel();
+        self.subscribers().push(Subscriber { prefix, sender });
+
+        receiver
+    }
+
+    fn notify(&self, event: ChangeEvent<T::Key, T::Value>) -> Result<(), T::Error> {
+        let key_bytes = T::key_to_bytes(event.key())?;
+        let key_bytes = key_bytes.as_ref();
+
+        self.subscribers().retain(|subscriber| {
+            !key_bytes.starts_with(&subscriber.prefix)
+                || subscriber.sender.send(event.clone()).is_ok()
+        });
+
+        Ok(())
+    }
+
+    fn subscribers(&self) -> MutexGuard<'_, Vec<Subscriber<T::Key, T::Value>>> {
+        // Subscribers are only added or removed whole, so a poisoned lock can still be used.
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
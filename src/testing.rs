@@ -0,0 +1,149 @@
+//! Helpers for testing table implementations (requires the `testing` feature).
+
+use crate::{mode, Table};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// Check a table's key and value encodings against sample entries.
+///
+/// This verifies that every key and value survives a round trip through its byte encoding, and
+/// that the order of encoded keys matches the order of the keys themselves (which is necessary for
+/// iteration and range queries to return entries in key order).
+///
+/// # Panics
+///
+/// Panics with a description of the first problem found.
+pub fn check_table<M, T: Table<M>>(samples: &[(T::Key, T::Value)])
+where
+    T::Key: Debug + Ord,
+    T::Value: Debug + PartialEq,
+    T::Error: Debug,
+{
+    let mut encoded_keys = Vec::with_capacity(samples.len());
+
+    for (key, value) in samples {
+        let key_bytes = T::key_to_bytes(key).expect("Key encoding failed");
+        let decoded_key =
+            T::bytes_to_key(Cow::from(key_bytes.as_ref())).expect("Key decoding failed");
+
+        assert_eq!(&decoded_key, key, "Key does not survive a round trip");
+
+        let value_bytes = T::value_to_bytes(value).expect("Value encoding failed");
+        let decoded_value =
+            T::bytes_to_value(Cow::from(value_bytes.as_ref())).expect("Value decoding failed");
+
+        assert_eq!(&decoded_value, value, "Value does not survive a round trip");
+
+        encoded_keys.push((key, key_bytes.as_ref().to_vec()));
+    }
+
+    encoded_keys.sort();
+
+    for pair in encoded_keys.windows(2) {
+        let (key_a, bytes_a) = &pair[0];
+        let (key_b, bytes_b) = &pair[1];
+
+        assert_eq!(
+            key_a.cmp(key_b),
+            bytes_a.cmp(bytes_b),
+            "Key encoding does not preserve the order of {:?} and {:?}",
+            key_a,
+            key_b
+        );
+    }
+}
+
+/// Write entries to a temporary table, and check that lookups and iteration return
+/// them as expected (later entries replace earlier entries with the same key).
+///
+/// # Panics
+///
+/// Panics with a description of the first problem found.
+pub fn table_round_trip<T: Table<mode::Writeable>>(entries: &[(T::Key, T::Value)])
+where
+    T::Key: Clone + Debug + Ord,
+    T::Value: Clone + Debug + PartialEq,
+    T::Error: Debug,
+{
+    let table = T::open_temporary(T::configure_options).expect("Opening table failed");
+    let expected = write_entries(&table, entries);
+
+    for (key, value) in &expected {
+        assert_eq!(
+            table.lookup_key(key).expect("Lookup failed").as_ref(),
+            Some(value),
+            "Lookup of {:?} returned the wrong value",
+            key
+        );
+    }
+
+    assert_eq!(
+        table
+            .iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Iteration failed"),
+        expected.into_iter().collect::<Vec<_>>(),
+        "Iteration returned the wrong entries"
+    );
+}
+
+/// Write entries to a temporary table, and check that index lookups return the
+/// entries for each index in key order, given a function that computes a key's index.
+///
+/// The encodings of distinct indices must not be prefixes of each other.
+///
+/// # Panics
+///
+/// Panics with a description of the first problem found.
+pub fn index_round_trip<T: Table<mode::Writeable>, F: Fn(&T::Key) -> T::Index>(
+    entries: &[(T::Key, T::Value)],
+    index_of: F,
+) where
+    T::Key: Clone + Debug + Ord,
+    T::Value: Clone + Debug + PartialEq,
+    T::Index: Debug + Ord,
+    T::Error: Debug,
+{
+    let table = T::open_temporary(T::configure_options).expect("Opening table failed");
+
+    let mut expected = BTreeMap::<T::Index, Vec<(T::Key, T::Value)>>::new();
+
+    for (key, value) in write_entries(&table, entries) {
+        expected
+            .entry(index_of(&key))
+            .or_default()
+            .push((key, value));
+    }
+
+    for (index, index_entries) in expected {
+        assert_eq!(
+            table
+                .lookup_index(&index)
+                .collect::<Result<Vec<_>, _>>()
+                .expect("Index lookup failed"),
+            index_entries,
+            "Lookup of index {:?} returned the wrong entries",
+            index
+        );
+    }
+}
+
+fn write_entries<T: Table<mode::Writeable>>(
+    table: &T,
+    entries: &[(T::Key, T::Value)],
+) -> BTreeMap<T::Key, T::Value>
+where
+    T::Key: Clone + Ord,
+    T::Value: Clone,
+    T::Error: Debug,
+{
+    let mut written = BTreeMap::new();
+
+    for (key, value) in entries {
+        table.put(key, value).expect("Write failed");
+        written.insert(key.clone(), value.clone());
+    }
+
+    written
+}
@@ -0,0 +1,32 @@
+//! Reports from verification scans that decode every entry in a table.
+
+/// An entry whose key or value couldn't be decoded.
+#[derive(Debug)]
+pub struct Undecodable<E> {
+    pub key_bytes: Vec<u8>,
+    pub error: E,
+}
+
+/// The result of a verification scan.
+#[derive(Debug)]
+pub struct VerifyReport<E> {
+    /// The number of entries examined.
+    pub scanned: u64,
+    pub undecodable: Vec<Undecodable<E>>,
+}
+
+impl<E> Default for VerifyReport<E> {
+    fn default() -> Self {
+        Self {
+            scanned: 0,
+            undecodable: vec![],
+        }
+    }
+}
+
+impl<E> VerifyReport<E> {
+    /// Whether every entry could be decoded.
+    pub fn is_ok(&self) -> bool {
+        self.undecodable.is_empty()
+    }
+}
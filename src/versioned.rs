@@ -0,0 +1,246 @@
+//! Values tagged with a one-byte format version, which are upgraded when they're read.
+//!
+//! The [`crate::impl_value_codec_via_versions`] macro generates a table's value codec methods for
+//! a value type that implements [`VersionedValue`]:
+//!
+//! ```ignore
+//! impl<M> Table<M> for Users<M> {
+//!     type Value = User;
+//!     type ValueBytes = Vec<u8>;
+//!
+//!     rocksdb_table::impl_value_codec_via_versions!();
+//!
+//!     // Key and index methods...
+//! }
+//! ```
+//!
+//! Old values are decoded with their own version's format, and [`upgrade`] rewrites them in the
+//! current format (values aren't upgraded during compaction, since compaction filters can only keep
+//! or remove entries).
+
+use crate::{error::Error, mode, Table};
+use rocksdb::WriteBatch;
+use std::borrow::Cow;
+
+/// A value type whose encoding has changed over time.
+pub trait VersionedValue: Sized {
+    /// The version of the format written by [`VersionedValue::encode_current`].
+    const VERSION: u8;
+
+    /// Encode the value in the current format (without the version tag).
+    fn encode_current(&self) -> Result<Vec<u8>, Error>;
+
+    /// Decode a value written in the given version's format (upgrading values in older formats).
+    fn decode_version(version: u8, bytes: &[u8]) -> Result<Self, Error>;
+}
+
+/// Encode a value in the current format, with its version tag.
+pub fn encode<V: VersionedValue>(value: &V) -> Result<Vec<u8>, Error> {
+    let encoded = value.encode_current()?;
+    let mut bytes = Vec::with_capacity(encoded.len() + 1);
+    bytes.push(V::VERSION);
+    bytes.extend_from_slice(&encoded);
+
+    Ok(bytes)
+}
+
+/// Decode a value in any version's format.
+pub fn decode<V: VersionedValue>(bytes: &[u8]) -> Result<V, Error> {
+    match bytes.split_first() {
+        Some((version, remaining)) => V::decode_version(*version, remaining),
+        None => Err(Error::InvalidValue(bytes.to_vec())),
+    }
+}
+
+/// The version tag of an encoded value.
+pub fn version(bytes: &[u8]) -> Option<u8> {
+    bytes.first().copied()
+}
+
+/// Rewrite every value that isn't in the current format, returning the number of values
+/// upgraded.
+///
+/// Values are read and rewritten in separate steps, so this shouldn't run while other writers are
+/// changing the table.
+pub fn upgrade<M: mode::IsWriteable, T: Table<M>>(table: &T) -> Result<u64, T::Error>
+where
+    T::Value: VersionedValue,
+{
+    let db = &table.database().db;
+    let mut batch = WriteBatch::default();
+    let mut count = 0;
+
+    let mut underlying = db.raw_iterator();
+    underlying.seek_to_first();
+
+    while let (Some(key_bytes), Some(value_bytes)) = (underlying.key(), underlying.value()) {
+        if version(value_bytes) != Some(T::Value::VERSION) {
+            let value = T::bytes_to_value(Cow::from(value_bytes))?;
+            batch.put(key_bytes, T::value_to_bytes(&value)?);
+            count += 1;
+
+            if batch.len() >= crate::PUT_ALL_BATCH_SIZE {
+                db.write(std::mem::take(&mut batch)).map_err(Error::from)?;
+            }
+        }
+
+        underlying.next();
+    }
+
+    underlying.status().map_err(Error::from)?;
+
+    if !batch.is_empty() {
+        db.write(batch).map_err(Error::from)?;
+    }
+
+    Ok(count)
+}
+
+/// Generate `value_to_bytes` and `bytes_to_value` for a table implementation whose value type
+/// implements [`crate::versioned::VersionedValue`] (the table's `ValueBytes` must be `Vec<u8>`).
+#[macro_export]
+macro_rules! impl_value_codec_via_versions {
+    () => {
+        #[allow(clippy::needless_question_mark)]
+        fn value_to_bytes(value: &Self::Value) -> Result<Self::ValueBytes, Self::Error> {
+            Ok($crate::versioned::encode(value)?)
+        }
+
+        #[allow(clippy::needless_question_mark)]
+        fn bytes_to_value(bytes: std::borrow::Cow<[u8]>) -> Result<Self::Value, Self::Error> {
+            Ok($crate::versioned::decode(&bytes)?)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Counter {
+        count: u64,
+        label: String,
+    }
+
+    impl VersionedValue for Counter {
+        const VERSION: u8 = 2;
+
+        fn encode_current(&self) -> Result<Vec<u8>, Error> {
+            let mut bytes = self.count.to_be_bytes().to_vec();
+            bytes.extend_from_slice(self.label.as_bytes());
+
+            Ok(bytes)
+        }
+
+        fn decode_version(version: u8, bytes: &[u8]) -> Result<Self, Error> {
+            let invalid = || Error::InvalidValue(bytes.to_vec());
+
+            match version {
+                // The first version only stored a 32-bit count.
+                1 => Ok(Self {
+                    count: u32::from_be_bytes(bytes.try_into().map_err(|_| invalid())?).into(),
+                    label: String::new(),
+                }),
+                2 => {
+                    let (count, label) = bytes.split_at_checked(8).ok_or_else(invalid)?;
+
+                    Ok(Self {
+                        count: u64::from_be_bytes(count.try_into().map_err(|_| invalid())?),
+                        label: String::from_utf8(label.to_vec()).map_err(|_| invalid())?,
+                    })
+                }
+                _ => Err(invalid()),
+            }
+        }
+    }
+
+    struct Counters<M> {
+        database: Database<M>,
+    }
+
+    impl<M> Table<M> for Counters<M> {
+        type Counts = ();
+        type Error = Error;
+        type Key = u64;
+        type KeyBytes = [u8; 8];
+        type Value = Counter;
+        type ValueBytes = Vec<u8>;
+        type Index = ();
+        type IndexBytes = [u8; 0];
+
+        fn database(&self) -> &Database<M> {
+            &self.database
+        }
+
+        fn from_database(database: Database<M>) -> Self {
+            Self { database }
+        }
+
+        fn get_counts(&self) -> Result<Self::Counts, Self::Error> {
+            Ok(())
+        }
+
+        fn key_to_bytes(key: &Self::Key) -> Result<Self::KeyBytes, Self::Error> {
+            Ok(key.to_be_bytes())
+        }
+
+        fn index_to_bytes(_index: &Self::Index) -> Result<Self::IndexBytes, Self::Error> {
+            Ok([])
+        }
+
+        fn bytes_to_key(bytes: Cow<[u8]>) -> Result<Self::Key, Self::Error> {
+            Ok(u64::from_be_bytes(
+                bytes
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| Error::InvalidKey(bytes.to_vec()))?,
+            ))
+        }
+
+        crate::impl_value_codec_via_versions!();
+    }
+
+    #[test]
+    fn upgrade_on_read() {
+        let directory = tempfile::tempdir().unwrap();
+        let counters = Counters::<mode::Writeable>::open_with_defaults(directory.path()).unwrap();
+
+        let counter = Counter {
+            count: 10,
+            label: "foo".to_string(),
+        };
+
+        counters.put(&1, &counter).unwrap();
+        counters
+            .database()
+            .db
+            .put(2u64.to_be_bytes(), [1, 0, 0, 0, 5])
+            .unwrap();
+
+        assert_eq!(counters.lookup_key(&1).unwrap(), Some(counter));
+        assert_eq!(
+            counters.lookup_key(&2).unwrap(),
+            Some(Counter {
+                count: 5,
+                label: String::new(),
+            })
+        );
+        assert!(decode::<Counter>(&[3, 0]).is_err());
+        assert!(decode::<Counter>(&[]).is_err());
+
+        assert_eq!(upgrade(&counters).unwrap(), 1);
+        assert_eq!(upgrade(&counters).unwrap(), 0);
+
+        let value_bytes = counters
+            .database()
+            .db
+            .get(2u64.to_be_bytes())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(version(&value_bytes), Some(2));
+        assert_eq!(counters.lookup_key(&2).unwrap().unwrap().count, 5);
+    }
+}
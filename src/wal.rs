@@ -0,0 +1,174 @@
+//! Decoding of write-ahead log updates, for change data capture.
+
+use crate::{error, Table};
+use rocksdb::{DBWALIterator, WriteBatchIterator, DB};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, VecDeque};
+use std::marker::PhantomData;
+
+/// An operation on a table's default column family.
+///
+/// RocksDB only reports puts and deletions to the default column family when iterating over a
+/// write batch, so merges are skipped, and a range deletion or a write to another column family
+/// ends the batch (skipping any later operations in it).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WalOperation<K, V> {
+    Put { key: K, value: V },
+    Delete { key: K },
+}
+
+/// An operation from the write-ahead log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalUpdate<K, V> {
+    /// The sequence number of the batch that contained the operation.
+    pub sequence: u64,
+    pub operation: WalOperation<K, V>,
+}
+
+type RawOperation = (Box<[u8]>, Option<Box<[u8]>>);
+
+#[derive(Default)]
+struct RawOperations(Vec<RawOperation>);
+
+impl WriteBatchIterator for RawOperations {
+    fn put(&mut self, key: Box<[u8]>, value: Box<[u8]>) {
+        self.0.push((key, Some(value)));
+    }
+
+    fn delete(&mut self, key: Box<[u8]>) {
+        self.0.push((key, None));
+    }
+}
+
+/// The keys put or deleted in batches after the given sequence number, up to the given last
+/// sequence number.
+///
+/// Fails with [`error::Error::IncompleteLog`] if any write in that range can't be read from the
+/// log, either because it wasn't logged or because its batch contains operations that can't be
+/// decoded (merges, range deletions, and writes to other column families).
+pub(crate) fn changed_keys(
+    db: &DB,
+    sequence: u64,
+    last_sequence: u64,
+) -> Result<BTreeSet<Vec<u8>>, error::Error> {
+    let mut keys = BTreeSet::new();
+    // The sequence number of the first write that hasn't been read yet.
+    let mut next_sequence = sequence + 1;
+
+    for result in db.get_updates_since(sequence)? {
+        let (batch_sequence, batch) = result?;
+
+        if batch_sequence > next_sequence && next_sequence <= last_sequence {
+            return Err(error::Error::IncompleteLog(next_sequence));
+        }
+
+        if batch_sequence > last_sequence {
+            break;
+        }
+
+        let mut operations = RawOperations::default();
+        batch.iterate(&mut operations);
+
+        if operations.0.len() < batch.len() {
+            return Err(error::Error::IncompleteLog(batch_sequence));
+        }
+
+        next_sequence = batch_sequence + batch.len() as u64;
+
+        keys.extend(
+            operations
+                .0
+                .into_iter()
+                .map(|(key_bytes, _)| key_bytes.into_vec()),
+        );
+    }
+
+    // The log iterator stops at the first gap, so this also catches gaps in the middle.
+    if next_sequence <= last_sequence {
+        return Err(error::Error::IncompleteLog(next_sequence));
+    }
+
+    Ok(keys)
+}
+
+/// Iterates over decoded operations from the write-ahead log, in order.
+///
+/// Decoding errors end the iteration.
+pub struct WalIterator<'a, M, T> {
+    // This is `None` after the iterator fails.
+    underlying: Option<DBWALIterator>,
+    sequence: u64,
+    pending: VecDeque<RawOperation>,
+    _database: PhantomData<&'a ()>,
+    _mode: PhantomData<M>,
+    _table: PhantomData<T>,
+}
+
+impl<M, T> WalIterator<'_, M, T> {
+    pub(crate) fn new(underlying: DBWALIterator) -> Self {
+        Self {
+            underlying: Some(underlying),
+            sequence: 0,
+            pending: VecDeque::new(),
+            _database: PhantomData,
+            _mode: PhantomData,
+            _table: PhantomData,
+        }
+    }
+}
+
+impl<M, T: Table<M>> WalIterator<'_, M, T> {
+    fn decode(
+        sequence: u64,
+        (key_bytes, value_bytes): RawOperation,
+    ) -> Result<WalUpdate<T::Key, T::Value>, T::Error> {
+        let key = T::bytes_to_key(Cow::from(Vec::from(key_bytes)))?;
+
+        let operation = match value_bytes {
+            Some(value_bytes) => WalOperation::Put {
+                key,
+                value: T::bytes_to_value(Cow::from(Vec::from(value_bytes)))?,
+            },
+            None => WalOperation::Delete { key },
+        };
+
+        Ok(WalUpdate {
+            sequence,
+            operation,
+        })
+    }
+}
+
+impl<M, T: Table<M>> Iterator for WalIterator<'_, M, T> {
+    type Item = Result<WalUpdate<T::Key, T::Value>, T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(operation) = self.pending.pop_front() {
+                let result = Self::decode(self.sequence, operation);
+
+                if result.is_err() {
+                    self.underlying = None;
+                    self.pending.clear();
+                }
+
+                return Some(result);
+            }
+
+            match self.underlying.as_mut()?.next()? {
+                Ok((sequence, batch)) => {
+                    let mut operations = RawOperations::default();
+                    batch.iterate(&mut operations);
+
+                    self.sequence = sequence;
+                    self.pending.extend(operations.0);
+                }
+                Err(error) => {
+                    self.underlying = None;
+
+                    return Some(Err(error::Error::from(error).into()));
+                }
+            }
+        }
+    }
+}